@@ -0,0 +1,93 @@
+//! Envelope encryption for token-bearing cache values
+//!
+//! Wraps any string before it is written to a `CacheBackend` so a Redis
+//! snapshot or memory dump doesn't leak live Moodle credentials. Values are
+//! stored as base64(nonce || ciphertext || tag).
+
+use aes_gcm::aead::{Aead, OsRng};
+use aes_gcm::{AeadCore, Aes256Gcm, KeyInit, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use secrecy::{ExposeSecret, SecretString};
+use sha2::{Digest, Sha256};
+use std::sync::OnceLock;
+
+use crate::config::Config;
+use crate::error::AppError;
+
+static CIPHER: OnceLock<TokenCipher> = OnceLock::new();
+
+/// Initialize the process-wide cache cipher from config
+///
+/// Must be called once at startup before any session/token is cached.
+pub fn init(config: &Config) {
+    CIPHER.set(TokenCipher::new(&config.cache_encryption_secret)).ok();
+}
+
+/// Encrypt a value for storage in the cache
+pub fn encrypt(plaintext: &str) -> Result<String, AppError> {
+    cipher().encrypt(plaintext)
+}
+
+/// Decrypt a value read back from the cache
+pub fn decrypt(encoded: &str) -> Result<String, AppError> {
+    cipher().decrypt(encoded)
+}
+
+fn cipher() -> &'static TokenCipher {
+    CIPHER.get().expect("cache::crypto::init must be called at startup")
+}
+
+/// AES-256-GCM cipher derived from a `Config`-supplied secret
+pub struct TokenCipher {
+    cipher: Aes256Gcm,
+}
+
+impl TokenCipher {
+    /// Derive a 256-bit key from the given secret via SHA-256
+    pub fn new(secret: &SecretString) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(secret.expose_secret().as_bytes());
+        let key = hasher.finalize();
+
+        Self {
+            cipher: Aes256Gcm::new_from_slice(&key).expect("SHA-256 output is always 32 bytes"),
+        }
+    }
+
+    /// Encrypt `plaintext`, returning base64(nonce || ciphertext || tag)
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, AppError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| AppError::Cache("Failed to encrypt cache value".to_string()))?;
+
+        let mut combined = nonce.to_vec();
+        combined.extend_from_slice(&ciphertext);
+
+        Ok(STANDARD.encode(combined))
+    }
+
+    /// Decrypt a value produced by `encrypt`
+    pub fn decrypt(&self, encoded: &str) -> Result<String, AppError> {
+        let combined = STANDARD
+            .decode(encoded)
+            .map_err(|e| AppError::Cache(format!("Invalid ciphertext encoding: {}", e)))?;
+
+        if combined.len() < 12 {
+            return Err(AppError::Cache("Ciphertext too short".to_string()));
+        }
+
+        let (nonce_bytes, ciphertext) = combined.split_at(12);
+        let nonce = Nonce::from_slice(nonce_bytes);
+
+        let plaintext = self
+            .cipher
+            .decrypt(nonce, ciphertext)
+            .map_err(|_| AppError::Cache("Cache value failed authentication (tampered or wrong key)".to_string()))?;
+
+        String::from_utf8(plaintext)
+            .map_err(|e| AppError::Cache(format!("Decrypted cache value is not valid UTF-8: {}", e)))
+    }
+}
@@ -0,0 +1,182 @@
+//! Disk-backed cache tier for the content pipeline
+//!
+//! Mirrors Deno's `HttpCache` layout: each entry is a body file plus a small
+//! sidecar metadata JSON, keyed by the same `url_hash` `MemoryCache` uses.
+//! Consulted by `routes::content` on a memory-cache miss so a server restart
+//! only costs a disk read instead of a full Moodle re-download and re-clean,
+//! and evicts least-recently-accessed entries once the directory grows past
+//! `Config::disk_cache_max_bytes`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::Config;
+
+/// Metadata stored alongside each cached body
+#[derive(Debug, Serialize, Deserialize)]
+struct Sidecar {
+    stored_at: u64,
+    accessed_at: u64,
+    ttl_secs: Option<u64>,
+}
+
+/// Disk-backed cache tier, rooted at a configurable directory
+struct DiskCache {
+    dir: PathBuf,
+    max_bytes: u64,
+}
+
+impl DiskCache {
+    fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            tracing::warn!("Failed to create disk cache dir {}: {}", dir.display(), e);
+        }
+        Self { dir, max_bytes }
+    }
+
+    fn body_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.body", key))
+    }
+
+    fn meta_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.meta.json", key))
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        let meta_path = self.meta_path(key);
+        let mut sidecar: Sidecar = serde_json::from_str(&std::fs::read_to_string(&meta_path).ok()?).ok()?;
+
+        if let Some(ttl_secs) = sidecar.ttl_secs {
+            if now_secs().saturating_sub(sidecar.stored_at) > ttl_secs {
+                self.delete(key);
+                return None;
+            }
+        }
+
+        let body = std::fs::read_to_string(self.body_path(key)).ok()?;
+
+        sidecar.accessed_at = now_secs();
+        if let Ok(json) = serde_json::to_string(&sidecar) {
+            let _ = std::fs::write(&meta_path, json);
+        }
+
+        Some(body)
+    }
+
+    fn set(&self, key: &str, value: &str, ttl: Option<Duration>) -> bool {
+        let sidecar = Sidecar {
+            stored_at: now_secs(),
+            accessed_at: now_secs(),
+            ttl_secs: ttl.map(|d| d.as_secs()),
+        };
+
+        let Ok(sidecar_json) = serde_json::to_string(&sidecar) else {
+            return false;
+        };
+        if std::fs::write(self.body_path(key), value).is_err() {
+            return false;
+        }
+        if std::fs::write(self.meta_path(key), sidecar_json).is_err() {
+            return false;
+        }
+
+        self.evict_over_budget();
+        true
+    }
+
+    fn delete(&self, key: &str) -> bool {
+        let body_removed = std::fs::remove_file(self.body_path(key)).is_ok();
+        let meta_removed = std::fs::remove_file(self.meta_path(key)).is_ok();
+        body_removed || meta_removed
+    }
+
+    fn clear(&self) -> bool {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return false;
+        };
+        for entry in entries.flatten() {
+            let _ = std::fs::remove_file(entry.path());
+        }
+        true
+    }
+
+    /// Evict least-recently-accessed entries (by sidecar `accessed_at`) until
+    /// the directory's total size is back under `max_bytes`
+    fn evict_over_budget(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+
+        struct Entry {
+            accessed_at: u64,
+            size: u64,
+        }
+
+        let mut by_key: HashMap<String, Entry> = HashMap::new();
+
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else { continue };
+            let path = entry.path();
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else { continue };
+            let Some(key) = file_name.split('.').next() else { continue };
+
+            let slot = by_key.entry(key.to_string()).or_insert(Entry { accessed_at: 0, size: 0 });
+            slot.size += metadata.len();
+
+            if file_name.ends_with(".meta.json") {
+                if let Ok(contents) = std::fs::read_to_string(&path) {
+                    if let Ok(sidecar) = serde_json::from_str::<Sidecar>(&contents) {
+                        slot.accessed_at = sidecar.accessed_at;
+                    }
+                }
+            }
+        }
+
+        let total: u64 = by_key.values().map(|e| e.size).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+
+        let mut ordered: Vec<(String, Entry)> = by_key.into_iter().collect();
+        ordered.sort_by_key(|(_, e)| e.accessed_at);
+
+        let mut remaining = total;
+        for (key, entry) in ordered {
+            if remaining <= self.max_bytes {
+                break;
+            }
+            remaining = remaining.saturating_sub(entry.size);
+            self.delete(&key);
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+static DISK: OnceLock<DiskCache> = OnceLock::new();
+
+/// Set up the disk cache tier from `Config`; must be called once at startup
+pub fn init(config: &Config) {
+    DISK.set(DiskCache::new(config.disk_cache_dir.clone(), config.disk_cache_max_bytes)).ok();
+}
+
+fn disk() -> &'static DiskCache {
+    DISK.get().expect("cache::disk::init must be called at startup")
+}
+
+pub async fn get(key: &str) -> Option<String> {
+    disk().get(key)
+}
+
+pub async fn set(key: &str, value: &str, ttl: Option<Duration>) -> bool {
+    disk().set(key, value, ttl)
+}
+
+pub async fn clear() -> bool {
+    disk().clear()
+}
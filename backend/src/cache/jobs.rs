@@ -0,0 +1,85 @@
+//! In-memory job state for background export tasks
+
+use std::collections::HashMap;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Status of a background export job
+#[derive(Debug, Clone)]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Done(Vec<u8>),
+    Failed(String),
+}
+
+struct JobEntry {
+    status: JobStatus,
+    expires_at: Instant,
+    /// Moodle userid of the requester who enqueued this job, checked by `get`
+    /// so one user can't poll/download another's export by guessing its id
+    owner_userid: i64,
+}
+
+/// In-memory store of background job state, keyed by job id
+pub struct JobStore {
+    jobs: RwLock<HashMap<String, JobEntry>>,
+}
+
+impl Default for JobStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobStore {
+    pub fn new() -> Self {
+        Self {
+            jobs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Register a freshly-enqueued job as `Pending`, owned by `owner_userid`
+    pub fn insert_pending(&self, job_id: &str, owner_userid: i64, ttl: Duration) {
+        self.set_status(job_id, owner_userid, JobStatus::Pending, ttl);
+    }
+
+    /// Overwrite a job's status, refreshing its expiry and owner
+    pub fn set_status(&self, job_id: &str, owner_userid: i64, status: JobStatus, ttl: Duration) {
+        if let Ok(mut jobs) = self.jobs.write() {
+            jobs.insert(
+                job_id.to_string(),
+                JobEntry {
+                    status,
+                    expires_at: Instant::now() + ttl,
+                    owner_userid,
+                },
+            );
+        }
+    }
+
+    /// Look up a job's current status, evicting it if its TTL has passed
+    ///
+    /// Returns `None` (same as an unknown job) if `requester_userid` doesn't
+    /// match the job's owner, so ownership checks fail closed as a 404
+    /// instead of leaking whether the job id exists.
+    pub fn get(&self, job_id: &str, requester_userid: i64) -> Option<JobStatus> {
+        let jobs = self.jobs.read().ok()?;
+        let entry = jobs.get(job_id)?;
+
+        if Instant::now() > entry.expires_at {
+            return None;
+        }
+
+        if entry.owner_userid != requester_userid {
+            return None;
+        }
+
+        Some(entry.status.clone())
+    }
+}
+
+/// Global job store instance
+lazy_static::lazy_static! {
+    pub static ref JOBS: JobStore = JobStore::new();
+}
@@ -1,45 +1,62 @@
 //! In-memory cache implementation
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
 use std::time::{Duration, Instant};
 
-/// In-memory cache with TTL support
+/// In-memory cache with TTL support and a byte-budgeted LRU eviction policy
 pub struct MemoryCache {
     store: RwLock<HashMap<String, CacheEntry>>,
+    max_bytes: u64,
+    used_bytes: AtomicU64,
 }
 
 struct CacheEntry {
     value: String,
     expires_at: Option<Instant>,
+    last_accessed: Instant,
+}
+
+impl CacheEntry {
+    /// Approximate heap footprint of this entry's key/value pair
+    fn size(key: &str, value: &str) -> u64 {
+        (key.len() + value.len()) as u64
+    }
 }
 
 impl Default for MemoryCache {
     fn default() -> Self {
-        Self::new()
+        Self::new(u64::MAX)
     }
 }
 
 impl MemoryCache {
-    pub fn new() -> Self {
+    /// Create a cache that evicts least-recently-used entries once the total
+    /// size of its keys/values would exceed `max_bytes`
+    pub fn new(max_bytes: u64) -> Self {
         Self {
             store: RwLock::new(HashMap::new()),
+            max_bytes,
+            used_bytes: AtomicU64::new(0),
         }
     }
 
     pub fn get(&self, key: &str) -> Option<String> {
-        let store = self.store.read().ok()?;
-        let entry = store.get(key)?;
-        
+        let mut store = self.store.write().ok()?;
+        let entry = store.get_mut(key)?;
+
         // Check if expired
         if let Some(expires_at) = entry.expires_at {
             if Instant::now() > expires_at {
-                drop(store);
-                self.delete(key);
+                let size = CacheEntry::size(key, &entry.value);
+                store.remove(key);
+                self.used_bytes.fetch_sub(size, Ordering::Relaxed);
                 return None;
             }
         }
-        
+
+        entry.last_accessed = Instant::now();
         Some(entry.value.clone())
     }
 
@@ -48,26 +65,60 @@ impl MemoryCache {
             Ok(s) => s,
             Err(_) => return false,
         };
-        
+
+        if let Some(old) = store.remove(key) {
+            self.used_bytes.fetch_sub(CacheEntry::size(key, &old.value), Ordering::Relaxed);
+        }
+
         let expires_at = ttl.map(|d| Instant::now() + d);
-        
+        let size = CacheEntry::size(key, value);
+
         store.insert(
             key.to_string(),
             CacheEntry {
                 value: value.to_string(),
                 expires_at,
+                last_accessed: Instant::now(),
             },
         );
-        
+        self.used_bytes.fetch_add(size, Ordering::Relaxed);
+
+        self.evict_over_budget(&mut store);
+
         true
     }
 
+    /// Evict least-recently-accessed entries until `used_bytes` is back
+    /// within `max_bytes`
+    fn evict_over_budget(&self, store: &mut HashMap<String, CacheEntry>) {
+        while self.used_bytes.load(Ordering::Relaxed) > self.max_bytes {
+            let Some(lru_key) = store
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_accessed)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+
+            if let Some(entry) = store.remove(&lru_key) {
+                self.used_bytes.fetch_sub(CacheEntry::size(&lru_key, &entry.value), Ordering::Relaxed);
+                tracing::debug!("Evicted LRU cache entry {} to stay within byte budget", lru_key);
+            }
+        }
+    }
+
     pub fn delete(&self, key: &str) -> bool {
         let mut store = match self.store.write() {
             Ok(s) => s,
             Err(_) => return false,
         };
-        store.remove(key).is_some()
+        match store.remove(key) {
+            Some(entry) => {
+                self.used_bytes.fetch_sub(CacheEntry::size(key, &entry.value), Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn has(&self, key: &str) -> bool {
@@ -80,6 +131,7 @@ impl MemoryCache {
             Err(_) => return false,
         };
         store.clear();
+        self.used_bytes.store(0, Ordering::Relaxed);
         true
     }
 
@@ -87,16 +139,34 @@ impl MemoryCache {
     pub fn url_hash(url: &str) -> String {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
-        
+
         let mut hasher = DefaultHasher::new();
         url.hash(&mut hasher);
         format!("{:x}", hasher.finish())
     }
 }
 
-/// Global cache instance
-lazy_static::lazy_static! {
-    pub static ref CACHE: MemoryCache = MemoryCache::new();
+#[async_trait::async_trait]
+impl super::CacheBackend for MemoryCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        self.get(key)
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Option<Duration>) -> bool {
+        self.set(key, value, ttl)
+    }
+
+    async fn delete(&self, key: &str) -> bool {
+        self.delete(key)
+    }
+
+    async fn has(&self, key: &str) -> bool {
+        self.has(key)
+    }
+
+    async fn clear(&self) -> bool {
+        self.clear()
+    }
 }
 
 #[cfg(test)]
@@ -105,14 +175,14 @@ mod tests {
 
     #[test]
     fn test_cache_set_get() {
-        let cache = MemoryCache::new();
+        let cache = MemoryCache::new(u64::MAX);
         cache.set("key1", "value1", None);
         assert_eq!(cache.get("key1"), Some("value1".to_string()));
     }
 
     #[test]
     fn test_cache_delete() {
-        let cache = MemoryCache::new();
+        let cache = MemoryCache::new(u64::MAX);
         cache.set("key1", "value1", None);
         cache.delete("key1");
         assert_eq!(cache.get("key1"), None);
@@ -120,9 +190,30 @@ mod tests {
 
     #[test]
     fn test_cache_has() {
-        let cache = MemoryCache::new();
+        let cache = MemoryCache::new(u64::MAX);
         cache.set("key1", "value1", None);
         assert!(cache.has("key1"));
         assert!(!cache.has("key2"));
     }
+
+    #[test]
+    fn test_cache_evicts_lru_when_over_budget() {
+        // Budget only fits one 10-byte entry (key + value, e.g. "key1" + "value1")
+        let cache = MemoryCache::new(10);
+        cache.set("key1", "value1", None);
+        cache.get("key1"); // touched before key2 is inserted, so key2 is still the more recently used
+        cache.set("key2", "value2", None);
+
+        assert!(cache.has("key2"));
+        assert!(!cache.has("key1"));
+    }
+
+    #[test]
+    fn test_cache_clear_resets_budget_accounting() {
+        let cache = MemoryCache::new(10);
+        cache.set("key1", "value1", None);
+        cache.clear();
+        cache.set("key2", "value2", None);
+        assert!(cache.has("key2"));
+    }
 }
@@ -1,11 +1,18 @@
 //! Caching module
 
 mod memory;
+pub mod crypto;
+pub mod disk;
+pub mod jobs;
+pub mod redis;
 
-pub use memory::*;
+pub use memory::MemoryCache;
 
+use std::sync::OnceLock;
 use std::time::Duration;
 
+use crate::config::Config;
+
 /// Cache trait for pluggable cache backends
 #[async_trait::async_trait]
 pub trait CacheBackend: Send + Sync {
@@ -15,3 +22,53 @@ pub trait CacheBackend: Send + Sync {
     async fn has(&self, key: &str) -> bool;
     async fn clear(&self) -> bool;
 }
+
+static BACKEND: OnceLock<Box<dyn CacheBackend>> = OnceLock::new();
+
+/// Select a `RedisCache` when `Config::redis_url` is set, else the in-memory cache
+///
+/// Must be called once at startup before any `get`/`set`/`delete`/`has`/`clear` call.
+pub async fn init(config: &Config) {
+    let backend: Box<dyn CacheBackend> = match &config.redis_url {
+        Some(url) => match redis::RedisCache::connect(url).await {
+            Ok(cache) => {
+                tracing::info!("Cache backend: Redis");
+                Box::new(cache)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to connect to Redis ({}), falling back to in-memory cache", e);
+                Box::new(MemoryCache::new(config.memory_cache_max_bytes))
+            }
+        },
+        None => {
+            tracing::info!("Cache backend: in-memory (set REDIS_URL to persist/share across instances)");
+            Box::new(MemoryCache::new(config.memory_cache_max_bytes))
+        }
+    };
+
+    BACKEND.set(backend).ok();
+}
+
+fn backend() -> &'static dyn CacheBackend {
+    BACKEND.get().expect("cache::init must be called at startup").as_ref()
+}
+
+pub async fn get(key: &str) -> Option<String> {
+    backend().get(key).await
+}
+
+pub async fn set(key: &str, value: &str, ttl: Option<Duration>) -> bool {
+    backend().set(key, value, ttl).await
+}
+
+pub async fn delete(key: &str) -> bool {
+    backend().delete(key).await
+}
+
+pub async fn has(key: &str) -> bool {
+    backend().has(key).await
+}
+
+pub async fn clear() -> bool {
+    backend().clear().await
+}
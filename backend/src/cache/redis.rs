@@ -0,0 +1,95 @@
+//! Redis-backed cache backend
+//!
+//! Persists cached values (site info, course contents, Typst-converted
+//! content) across restarts and shares them across instances, at the cost of
+//! a network round trip `MemoryCache` doesn't pay. Selected by `cache::init`
+//! when `Config::redis_url` is set; falls back to `MemoryCache` if the
+//! connection can't be established at startup.
+
+use std::time::Duration;
+
+use redis::AsyncCommands;
+
+use super::CacheBackend;
+
+/// Key prefixes this app owns in the shared Redis database - `clear()` only
+/// evicts keys under these, so it can't be used to wipe a logical database
+/// shared with other services. `session:` is one of them, so `clear()` also
+/// evicts every login session.
+const OWNED_KEY_PREFIXES: &[&str] = &["activity:", "session:", "embedding:", "media:"];
+
+pub struct RedisCache {
+    manager: redis::aio::ConnectionManager,
+}
+
+impl RedisCache {
+    /// Connect to `url`, failing fast at startup rather than on the first cache hit
+    pub async fn connect(url: &str) -> redis::RedisResult<Self> {
+        let client = redis::Client::open(url)?;
+        let manager = client.get_connection_manager().await?;
+        Ok(Self { manager })
+    }
+}
+
+#[async_trait::async_trait]
+impl CacheBackend for RedisCache {
+    async fn get(&self, key: &str) -> Option<String> {
+        let mut conn = self.manager.clone();
+        conn.get(key).await.ok()
+    }
+
+    async fn set(&self, key: &str, value: &str, ttl: Option<Duration>) -> bool {
+        let mut conn = self.manager.clone();
+        let result: redis::RedisResult<()> = match ttl {
+            Some(ttl) => conn.set_ex(key, value, ttl.as_secs()).await,
+            None => conn.set(key, value).await,
+        };
+        result.is_ok()
+    }
+
+    async fn delete(&self, key: &str) -> bool {
+        let mut conn = self.manager.clone();
+        conn.del::<_, i64>(key).await.map(|deleted| deleted > 0).unwrap_or(false)
+    }
+
+    async fn has(&self, key: &str) -> bool {
+        let mut conn = self.manager.clone();
+        conn.exists(key).await.unwrap_or(false)
+    }
+
+    /// Evict every key this app owns (see `OWNED_KEY_PREFIXES`), scanning
+    /// rather than issuing `FLUSHDB` so other data sharing this Redis logical
+    /// database is left untouched
+    async fn clear(&self) -> bool {
+        let mut conn = self.manager.clone();
+
+        for prefix in OWNED_KEY_PREFIXES {
+            let mut cursor: u64 = 0;
+            loop {
+                let (next_cursor, keys): (u64, Vec<String>) = match redis::cmd("SCAN")
+                    .arg(cursor)
+                    .arg("MATCH")
+                    .arg(format!("{}*", prefix))
+                    .arg("COUNT")
+                    .arg(200)
+                    .query_async(&mut conn)
+                    .await
+                {
+                    Ok(result) => result,
+                    Err(_) => return false,
+                };
+
+                if !keys.is_empty() && conn.del::<_, i64>(&keys).await.is_err() {
+                    return false;
+                }
+
+                cursor = next_cursor;
+                if cursor == 0 {
+                    break;
+                }
+            }
+        }
+
+        true
+    }
+}
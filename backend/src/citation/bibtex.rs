@@ -0,0 +1,142 @@
+//! BibTeX parsing - `@type{key, field = {value}, ...}`
+
+use std::collections::HashMap;
+
+use super::types::BibEntry;
+
+/// Parse zero or more BibTeX entries out of a `.bib` source string
+pub fn parse_bibtex(input: &str) -> Vec<BibEntry> {
+    let mut entries = Vec::new();
+    let mut cursor = 0usize;
+
+    while let Some(rel_at) = input[cursor..].find('@') {
+        let at_pos = cursor + rel_at;
+        let after_at = &input[at_pos + 1..];
+
+        let Some(rel_brace) = after_at.find('{') else { break };
+        let brace_pos = at_pos + 1 + rel_brace;
+        let entry_type = input[at_pos + 1..brace_pos].trim().to_lowercase();
+
+        let Some(end_pos) = find_matching_brace(input, brace_pos) else { break };
+        let body = &input[brace_pos + 1..end_pos];
+
+        if let Some((key_part, fields_part)) = body.split_once(',') {
+            let key = key_part.trim().to_string();
+            let mut fields = HashMap::new();
+
+            for field in split_top_level(fields_part, ',') {
+                if let Some((name, value)) = field.split_once('=') {
+                    fields.insert(name.trim().to_lowercase(), strip_braces_quotes(value));
+                }
+            }
+
+            entries.push(BibEntry { key, entry_type, fields });
+        }
+
+        cursor = end_pos + 1;
+        if cursor >= input.len() {
+            break;
+        }
+    }
+
+    entries
+}
+
+/// Find the index of the `}` that closes the `{` at `open_idx`, accounting for nesting
+fn find_matching_brace(s: &str, open_idx: usize) -> Option<usize> {
+    let mut depth = 0i32;
+    for (i, b) in s.bytes().enumerate().skip(open_idx) {
+        match b {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Split on `sep` only at brace-depth zero and outside quotes, so `{a, b}` stays one field
+fn split_top_level(s: &str, sep: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut in_quotes = false;
+    let mut current = String::new();
+
+    for c in s.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            '{' if !in_quotes => {
+                depth += 1;
+                current.push(c);
+            }
+            '}' if !in_quotes => {
+                depth -= 1;
+                current.push(c);
+            }
+            c if c == sep && depth == 0 && !in_quotes => {
+                parts.push(current.trim().to_string());
+                current = String::new();
+            }
+            c => current.push(c),
+        }
+    }
+
+    if !current.trim().is_empty() {
+        parts.push(current.trim().to_string());
+    }
+
+    parts
+}
+
+/// Strip a single layer of surrounding `{}` or `""` from a field value
+fn strip_braces_quotes(value: &str) -> String {
+    let v = value.trim();
+    if v.len() >= 2
+        && ((v.starts_with('{') && v.ends_with('}')) || (v.starts_with('"') && v.ends_with('"')))
+    {
+        v[1..v.len() - 1].to_string()
+    } else {
+        v.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_entry() {
+        let bib = r#"@article{ribeiro2020, author = {Ribeiro, M.}, title = {Explaining Models}, year = {2020}}"#;
+        let entries = parse_bibtex(bib);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].key, "ribeiro2020");
+        assert_eq!(entries[0].entry_type, "article");
+        assert_eq!(entries[0].field("title"), Some("Explaining Models"));
+        assert_eq!(entries[0].field("year"), Some("2020"));
+    }
+
+    #[test]
+    fn test_parse_nested_braces() {
+        let bib = r#"@book{knuth1997, title = {The Art of {Computer} Programming}, author = "Knuth, Donald"}"#;
+        let entries = parse_bibtex(bib);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].field("title"), Some("The Art of {Computer} Programming"));
+        assert_eq!(entries[0].field("author"), Some("Knuth, Donald"));
+    }
+
+    #[test]
+    fn test_parse_multiple_entries() {
+        let bib = "@article{a1, title = {One}}\n\n@article{a2, title = {Two}}";
+        let entries = parse_bibtex(bib);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].key, "a2");
+    }
+}
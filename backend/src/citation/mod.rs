@@ -0,0 +1,76 @@
+//! Bibliography/citation subsystem - BibTeX/RIS parsing and LibGen -> BibEntry synthesis
+//!
+//! Parallel to the `libgen` module: where `libgen` finds a book, `citation`
+//! turns it (or a pasted BibTeX/RIS record) into a `BibEntry` that
+//! `content::typst::generate_typst_document` can render as a Typst
+//! `#bibliography(...)`.
+
+mod bibtex;
+mod ris;
+mod types;
+
+pub use bibtex::parse_bibtex;
+pub use ris::parse_ris;
+pub use types::BibEntry;
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use crate::libgen::Book;
+
+/// Filename `#bibliography(...)` is told to load, relative to the Typst document
+pub const BIBLIOGRAPHY_FILENAME: &str = "refs.bib";
+
+/// Serialize entries as BibTeX text, written alongside a Typst export
+pub fn to_bibtex(entries: &[BibEntry]) -> String {
+    let mut out = String::new();
+
+    for entry in entries {
+        out.push_str(&format!("@{}{{{},\n", entry.entry_type, entry.key));
+        for (name, value) in &entry.fields {
+            out.push_str(&format!("  {} = {{{}}},\n", name, value));
+        }
+        out.push_str("}\n\n");
+    }
+
+    out
+}
+
+/// Write `entries` as `refs.bib` inside `dir`, returning the filename for `#bibliography(...)`
+pub fn write_bibtex(dir: &Path, entries: &[BibEntry]) -> io::Result<&'static str> {
+    std::fs::write(dir.join(BIBLIOGRAPHY_FILENAME), to_bibtex(entries))?;
+    Ok(BIBLIOGRAPHY_FILENAME)
+}
+
+/// Synthesize a `BibEntry` from a chosen LibGen search result
+pub fn from_book(book: &Book, key: &str) -> BibEntry {
+    let mut fields = HashMap::new();
+    fields.insert("title".to_string(), book.title.clone());
+    fields.insert("author".to_string(), book.author.clone());
+
+    if let Some(year) = &book.year {
+        fields.insert("year".to_string(), year.clone());
+    }
+    if let Some(publisher) = &book.publisher {
+        fields.insert("publisher".to_string(), publisher.clone());
+    }
+
+    BibEntry {
+        key: key.to_string(),
+        entry_type: "book".to_string(),
+        fields,
+    }
+}
+
+/// Replace `[[cite:key]]` markers left in section content with Typst's `[@key]` citation syntax
+pub fn insert_markers(content: &str, entries: &[BibEntry]) -> String {
+    let mut result = content.to_string();
+
+    for entry in entries {
+        let marker = format!("[[cite:{}]]", entry.key);
+        result = result.replace(&marker, &format!("[@{}]", entry.key));
+    }
+
+    result
+}
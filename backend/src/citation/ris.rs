@@ -0,0 +1,107 @@
+//! RIS parsing - `TY  - JOUR` ... `ER  -` delimited records, as exposed by LibGen
+
+use std::collections::HashMap;
+
+use super::types::BibEntry;
+
+/// Map an RIS entry type tag to a BibTeX-ish entry type
+fn entry_type_for_ris(ty: &str) -> String {
+    match ty {
+        "JOUR" => "article",
+        "BOOK" => "book",
+        "CHAP" => "incollection",
+        "CONF" => "inproceedings",
+        _ => "misc",
+    }
+    .to_string()
+}
+
+/// Map an RIS field tag to a BibTeX-ish field name
+fn field_name_for_tag(tag: &str) -> Option<&'static str> {
+    Some(match tag {
+        "AU" | "A1" => "author",
+        "TI" | "T1" => "title",
+        "PY" | "Y1" => "year",
+        "PB" => "publisher",
+        "JO" | "JF" | "T2" => "journal",
+        "SP" => "pages",
+        "VL" => "volume",
+        "IS" => "number",
+        "DO" => "doi",
+        "UR" => "url",
+        _ => return None,
+    })
+}
+
+/// Parse zero or more RIS entries out of a `.ris` source string
+pub fn parse_ris(input: &str) -> Vec<BibEntry> {
+    let mut entries = Vec::new();
+    let mut entry_type = String::new();
+    let mut fields: HashMap<String, String> = HashMap::new();
+    let mut untagged_count = 0usize;
+
+    for line in input.lines() {
+        let line = line.trim_end();
+        let Some((tag, rest)) = line.split_once("  -") else { continue };
+        let tag = tag.trim();
+        let value = rest.trim();
+
+        if tag == "TY" {
+            entry_type = entry_type_for_ris(value);
+            fields.clear();
+            continue;
+        }
+
+        if tag == "ER" {
+            untagged_count += 1;
+            let key = fields
+                .get("author")
+                .and_then(|a| a.split(|c: char| c == ',' || c.is_whitespace()).next())
+                .filter(|s| !s.is_empty())
+                .map(|last| format!("{}{}", last.to_lowercase(), fields.get("year").cloned().unwrap_or_default()))
+                .unwrap_or_else(|| format!("ris{}", untagged_count));
+
+            entries.push(BibEntry {
+                key,
+                entry_type: entry_type.clone(),
+                fields: fields.clone(),
+            });
+            continue;
+        }
+
+        if let Some(field_name) = field_name_for_tag(tag) {
+            fields
+                .entry(field_name.to_string())
+                .and_modify(|v| {
+                    v.push_str(", ");
+                    v.push_str(value);
+                })
+                .or_insert_with(|| value.to_string());
+        }
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_entry() {
+        let ris = "TY  - JOUR\nAU  - Smith, John\nTI  - A Study\nPY  - 2019\nER  - \n";
+        let entries = parse_ris(ris);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].entry_type, "article");
+        assert_eq!(entries[0].field("title"), Some("A Study"));
+        assert_eq!(entries[0].key, "smith2019");
+    }
+
+    #[test]
+    fn test_parse_multiple_entries() {
+        let ris = "TY  - BOOK\nAU  - Doe, Jane\nTI  - First\nPY  - 2000\nER  - \nTY  - BOOK\nAU  - Doe, Jane\nTI  - Second\nPY  - 2005\nER  - \n";
+        let entries = parse_ris(ris);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].field("title"), Some("Second"));
+    }
+}
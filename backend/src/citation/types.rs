@@ -0,0 +1,19 @@
+//! Citation entry types shared by the BibTeX/RIS parsers and Typst export
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+/// A single bibliography entry, parsed from BibTeX/RIS or synthesized from a LibGen result
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BibEntry {
+    pub key: String,
+    pub entry_type: String,
+    pub fields: HashMap<String, String>,
+}
+
+impl BibEntry {
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields.get(name).map(|s| s.as_str())
+    }
+}
@@ -1,6 +1,21 @@
 //! Application configuration loaded from environment variables
 
+use secrecy::SecretString;
 use std::env;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default cap on the content disk cache before LRU eviction kicks in (500 MiB)
+const DEFAULT_DISK_CACHE_MAX_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Default cap on the in-memory cache before LRU eviction kicks in (256 MiB)
+const DEFAULT_MEMORY_CACHE_MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Default ceiling on a single Moodle file download (4 MiB)
+const DEFAULT_MAX_DOWNLOAD_BYTES: usize = 4 * 1024 * 1024;
+
+/// Default timeout for a single Moodle file download
+const DEFAULT_DOWNLOAD_TIMEOUT_SECS: u64 = 10;
 
 /// Application configuration
 #[derive(Clone, Debug)]
@@ -13,6 +28,20 @@ pub struct Config {
     pub moodle_url: String,
     /// Redis URL for caching (optional)
     pub redis_url: Option<String>,
+    /// Secret used to sign session cookies (HMAC-SHA256)
+    pub session_secret: String,
+    /// Secret used to derive the AES-256-GCM key for encrypting cached tokens/sessions at rest
+    pub cache_encryption_secret: SecretString,
+    /// Directory the content pipeline's disk cache tier is rooted at
+    pub disk_cache_dir: PathBuf,
+    /// Size cap (bytes) for the content disk cache before LRU eviction kicks in
+    pub disk_cache_max_bytes: u64,
+    /// Size cap (bytes) for the in-memory cache before LRU eviction kicks in
+    pub memory_cache_max_bytes: u64,
+    /// Ceiling (bytes) on a single file downloaded from Moodle before aborting with `ContentTooLarge`
+    pub max_download_bytes: usize,
+    /// Timeout for a single file download from Moodle
+    pub download_timeout: Duration,
 }
 
 impl Config {
@@ -27,6 +56,32 @@ impl Config {
             moodle_url: env::var("MOODLE_URL")
                 .unwrap_or_else(|_| "https://mylms.vossie.net".to_string()),
             redis_url: env::var("REDIS_URL").ok(),
+            session_secret: env::var("SESSION_SECRET")
+                .unwrap_or_else(|_| "dev-insecure-session-secret-change-me".to_string()),
+            cache_encryption_secret: SecretString::from(
+                env::var("CACHE_ENCRYPTION_SECRET")
+                    .unwrap_or_else(|_| "dev-insecure-cache-secret-change-me".to_string()),
+            ),
+            disk_cache_dir: env::var("CONTENT_DISK_CACHE_DIR")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("./cache-data/content")),
+            disk_cache_max_bytes: env::var("CONTENT_DISK_CACHE_MAX_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_DISK_CACHE_MAX_BYTES),
+            memory_cache_max_bytes: env::var("MEMORY_CACHE_MAX_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MEMORY_CACHE_MAX_BYTES),
+            max_download_bytes: env::var("MAX_DOWNLOAD_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_DOWNLOAD_BYTES),
+            download_timeout: env::var("DOWNLOAD_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(Duration::from_secs(DEFAULT_DOWNLOAD_TIMEOUT_SECS)),
         }
     }
 
@@ -4,6 +4,21 @@
 //! iframes, and Kortext/Prescribed Reading boilerplate.
 
 use lol_html::{element, rewrite_str, RewriteStrSettings};
+use ego_tree::NodeId;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+/// Class/id substrings that boost a candidate element's readability score
+const POSITIVE_PATTERNS: &[&str] = &["article", "content", "main", "body"];
+
+/// Class/id substrings that penalize a candidate element's readability score
+const NEGATIVE_PATTERNS: &[&str] = &["nav", "sidebar", "footer", "comment", "breadcrumb"];
+
+/// Tags considered as readability candidates
+const CANDIDATE_TAGS: &[&str] = &["p", "div", "section", "article", "td"];
+
+/// Minimum winning score before `extract_main_content` trusts its pick over the whole document
+const MIN_CONTENT_SCORE: f64 = 20.0;
 
 /// Kortext phrases - containers with these phrases are removed
 const KORTEXT_PHRASES: &[&str] = &[
@@ -30,17 +45,21 @@ const CONTAINER_CLASSES: &[&str] = &[
 
 /// Clean HTML content
 pub fn clean_html_content(html: &str) -> String {
-    clean_html_with_token(html, None)
+    clean_html_with_token(html, None, false)
 }
 
-/// Clean HTML content and optionally fix image URLs with token
-pub fn clean_html_with_token(html: &str, token: Option<&str>) -> String {
+/// Clean HTML content, optionally fixing image URLs with a token and/or
+/// narrowing the output to the page's main content before cleaning
+pub fn clean_html_with_token(html: &str, token: Option<&str>, extract_main: bool) -> String {
     if html.is_empty() {
         return String::new();
     }
 
     let original_len = html.len();
-    
+
+    let html = if extract_main { extract_main_content(html) } else { html.to_string() };
+    let html = html.as_str();
+
     // First pass: streaming removal of definitely unwanted elements
     let result = rewrite_str(
         html,
@@ -140,6 +159,98 @@ pub fn clean_html_with_token(html: &str, token: Option<&str>) -> String {
     output
 }
 
+/// Narrow a page down to its main content using a readability-style scoring pass
+///
+/// Walks `p`/`div`/`section`/`article`/`td` elements, scoring each on inner
+/// text length (capped) and comma count, penalizing high link density, and
+/// boosting/penalizing class/id matches against `POSITIVE_PATTERNS`/
+/// `NEGATIVE_PATTERNS`. A fraction of each score propagates up to the parent
+/// and grandparent so the real content container - not its most verbose
+/// paragraph - tends to win. Falls back to the untouched document when the
+/// top score doesn't clear `MIN_CONTENT_SCORE`, so short pages survive.
+pub fn extract_main_content(html: &str) -> String {
+    let document = Html::parse_document(html);
+    let Ok(candidate_selector) = Selector::parse(&CANDIDATE_TAGS.join(",")) else {
+        return html.to_string();
+    };
+
+    // Keyed by NodeId (node identity), not `element.html()` (serialized
+    // markup) - structurally-identical boilerplate elements share the same
+    // markup and would otherwise collide onto one key, summing their scores
+    // into a single entry that can out-score the real, unique article body.
+    let mut scores: HashMap<NodeId, f64> = HashMap::new();
+    let mut elements: HashMap<NodeId, ElementRef> = HashMap::new();
+
+    for candidate in document.select(&candidate_selector) {
+        let score = score_element(&candidate);
+        if score <= 0.0 {
+            continue;
+        }
+
+        bump_score(&mut scores, &mut elements, &candidate, score);
+        if let Some(parent) = candidate.parent().and_then(ElementRef::wrap) {
+            bump_score(&mut scores, &mut elements, &parent, score * 0.5);
+            if let Some(grandparent) = parent.parent().and_then(ElementRef::wrap) {
+                bump_score(&mut scores, &mut elements, &grandparent, score * 0.25);
+            }
+        }
+    }
+
+    match scores.into_iter().max_by(|a, b| a.1.total_cmp(&b.1)) {
+        Some((id, score)) if score >= MIN_CONTENT_SCORE => elements[&id].html(),
+        _ => html.to_string(),
+    }
+}
+
+fn bump_score<'a>(
+    scores: &mut HashMap<NodeId, f64>,
+    elements: &mut HashMap<NodeId, ElementRef<'a>>,
+    element: &ElementRef<'a>,
+    amount: f64,
+) {
+    *scores.entry(element.id()).or_insert(0.0) += amount;
+    elements.entry(element.id()).or_insert(*element);
+}
+
+/// Score one readability candidate: length/comma signal, link-density penalty, class/id bias
+fn score_element(element: &ElementRef) -> f64 {
+    let text: String = element.text().collect();
+    let text_len = text.trim().len();
+    if text_len == 0 {
+        return 0.0;
+    }
+
+    let comma_count = text.matches(',').count();
+    let mut score = (comma_count as f64 + text_len as f64 / 100.0).min(30.0);
+
+    if let Ok(link_selector) = Selector::parse("a") {
+        let link_text_len: usize = element
+            .select(&link_selector)
+            .map(|a| a.text().collect::<String>().len())
+            .sum();
+        let link_density = link_text_len as f64 / text_len as f64;
+        if link_density > 0.3 {
+            score -= score * link_density;
+        }
+    }
+
+    let class_and_id = format!(
+        "{} {}",
+        element.value().attr("class").unwrap_or(""),
+        element.value().attr("id").unwrap_or("")
+    )
+    .to_lowercase();
+
+    if POSITIVE_PATTERNS.iter().any(|p| class_and_id.contains(p)) {
+        score += 25.0;
+    }
+    if NEGATIVE_PATTERNS.iter().any(|p| class_and_id.contains(p)) {
+        score -= 25.0;
+    }
+
+    score
+}
+
 /// Remove containers that have unwanted Kortext/Prescribed Reading content
 fn remove_unwanted_containers(html: &str) -> String {
     use scraper::{Html, Selector};
@@ -258,36 +369,44 @@ fn remove_empty_paragraphs(html: &str) -> String {
 
 /// Fix image URLs to include authentication token
 fn fix_image_urls(html: &str, token: &str) -> String {
-    let mut result = html.to_string();
-    
-    if let Ok(re) = regex::Regex::new(r#"(<img[^>]+src=["'])([^"']+)(["'])"#) {
-        result = re.replace_all(&result, |caps: &regex::Captures| {
-            let prefix = &caps[1];
-            let url = &caps[2];
-            let suffix = &caps[3];
-            
-            // Skip if already has token
-            if url.contains("token=") {
-                return caps[0].to_string();
-            }
-            
-            // Skip data URIs
-            if url.starts_with("data:") {
-                return caps[0].to_string();
-            }
-            
-            // Skip external URLs (not Moodle)
-            if url.starts_with("http") && !url.contains("mylms.vossie.net") {
-                return caps[0].to_string();
-            }
-            
-            // Add token to Moodle URLs or relative URLs
-            let separator = if url.contains('?') { "&" } else { "?" };
-            format!("{}{}{}token={}{}", prefix, url, separator, token, suffix)
-        }).to_string();
-    }
-    
-    result
+    rewrite_image_srcs(html, |url| {
+        // Skip if already has token
+        if url.contains("token=") {
+            return None;
+        }
+
+        // Skip data URIs
+        if url.starts_with("data:") {
+            return None;
+        }
+
+        // Skip external URLs (not Moodle)
+        if url.starts_with("http") && !url.contains("mylms.vossie.net") {
+            return None;
+        }
+
+        // Add token to Moodle URLs or relative URLs
+        let separator = if url.contains('?') { "&" } else { "?" };
+        Some(format!("{}{}token={}", url, separator, token))
+    })
+}
+
+/// Rewrite every `<img src="...">` attribute via `rewrite`, which receives the
+/// original URL and returns `Some(new_url)` to replace it or `None` to leave
+/// it untouched. Shared by `fix_image_urls` (Moodle token injection) and the
+/// EPUB exporter (downloaded-image resource embedding).
+pub(crate) fn rewrite_image_srcs(html: &str, mut rewrite: impl FnMut(&str) -> Option<String>) -> String {
+    let Ok(re) = regex::Regex::new(r#"(<img[^>]+src=["'])([^"']+)(["'])"#) else {
+        return html.to_string();
+    };
+
+    re.replace_all(html, |caps: &regex::Captures| {
+        let (prefix, url, suffix) = (&caps[1], &caps[2], &caps[3]);
+        match rewrite(url) {
+            Some(new_url) => format!("{}{}{}", prefix, new_url, suffix),
+            None => caps[0].to_string(),
+        }
+    }).to_string()
 }
 
 #[cfg(test)]
@@ -0,0 +1,9 @@
+//! Content processing module - HTML cleaning and Typst conversion
+
+pub mod cleaner;
+pub mod reader;
+pub mod typst;
+
+pub(crate) use cleaner::rewrite_image_srcs;
+pub use cleaner::{clean_html_content, clean_html_with_token, extract_main_content};
+pub use reader::extract_reader_content;
@@ -0,0 +1,203 @@
+//! Reader-mode content extraction
+//!
+//! Moodle-embedded HTML carries navigation chrome, tracking widgets, and
+//! inline boilerplate that survive the regular cleaning pass in `cleaner.rs`.
+//! This module applies a second, more aggressive pass on top of that: a
+//! cosmetic-filter step that drops whole subtrees matching a fixed set of
+//! CSS selectors (ads, headers, footers, scripts/styles), then walks what's
+//! left into a simplified structure of headings/paragraphs/links/images
+//! before serializing back to minimal HTML. Containers themselves, empty or
+//! not, are never emitted by that walk.
+
+use scraper::{ElementRef, Html, Selector};
+
+/// CSS selectors for chrome dropped before the tree walk
+const COSMETIC_SELECTORS: &[&str] = &[
+    "script",
+    "style",
+    "nav",
+    "header",
+    "footer",
+    "aside",
+    ".ad",
+    ".ads",
+    ".advert",
+    ".advertisement",
+    ".navigation",
+    ".breadcrumb",
+    ".activity-navigation",
+    "#page-header",
+    ".modified",
+];
+
+/// One simplified reader-mode node
+enum ReaderNode {
+    Heading { level: u8, text: String },
+    Paragraph { text: String },
+    Link { href: String, text: String },
+    Image { src: String, alt: String },
+}
+
+/// Strip cosmetic chrome from `html`, then extract a simplified structure of
+/// headings/paragraphs/links/images and serialize it back to minimal HTML
+pub fn extract_reader_content(html: &str) -> String {
+    if html.is_empty() {
+        return String::new();
+    }
+
+    let stripped = strip_cosmetic_chrome(html);
+    let document = Html::parse_document(&stripped);
+    let nodes = walk(&document);
+
+    render_nodes(&nodes)
+}
+
+/// Remove elements matching `COSMETIC_SELECTORS`, re-parsing after each
+/// selector so later ones see the narrowed document - same approach as
+/// `cleaner::remove_unwanted_containers`
+fn strip_cosmetic_chrome(html: &str) -> String {
+    let mut output = html.to_string();
+
+    for selector_str in COSMETIC_SELECTORS {
+        let Ok(selector) = Selector::parse(selector_str) else {
+            continue;
+        };
+
+        let document = Html::parse_document(&output);
+        for element in document.select(&selector) {
+            let element_html = element.html();
+            if !element_html.trim().is_empty() {
+                output = output.replace(&element_html, "");
+            }
+        }
+    }
+
+    output
+}
+
+/// Walk the document in source order, collecting headings/paragraphs/images
+/// and any links not already captured as part of a paragraph's text
+fn walk(document: &Html) -> Vec<ReaderNode> {
+    let mut nodes = Vec::new();
+
+    let Ok(selector) = Selector::parse("h1,h2,h3,h4,h5,h6,p,img,a") else {
+        return nodes;
+    };
+
+    for element in document.select(&selector) {
+        let tag = element.value().name();
+
+        if tag == "a" && element.ancestors().any(|a| ElementRef::wrap(a).is_some_and(|e| e.value().name() == "p")) {
+            continue;
+        }
+
+        match tag {
+            "img" => {
+                let src = element.value().attr("src").unwrap_or("").to_string();
+                if src.is_empty() {
+                    continue;
+                }
+                let alt = element.value().attr("alt").unwrap_or("").to_string();
+                nodes.push(ReaderNode::Image { src, alt });
+            }
+            "a" => {
+                let href = element.value().attr("href").unwrap_or("").to_string();
+                let text: String = element.text().collect::<String>().trim().to_string();
+                if href.is_empty() || text.is_empty() {
+                    continue;
+                }
+                nodes.push(ReaderNode::Link { href, text });
+            }
+            "p" => {
+                let text: String = element.text().collect::<String>().trim().to_string();
+                if text.is_empty() {
+                    continue;
+                }
+                nodes.push(ReaderNode::Paragraph { text });
+            }
+            heading => {
+                let text: String = element.text().collect::<String>().trim().to_string();
+                if text.is_empty() {
+                    continue;
+                }
+                let level = heading.get(1..).and_then(|n| n.parse().ok()).unwrap_or(2);
+                nodes.push(ReaderNode::Heading { level, text });
+            }
+        }
+    }
+
+    nodes
+}
+
+/// Serialize simplified reader nodes back to minimal, semantic HTML
+fn render_nodes(nodes: &[ReaderNode]) -> String {
+    let mut output = String::new();
+
+    for node in nodes {
+        match node {
+            ReaderNode::Heading { level, text } => {
+                output.push_str(&format!("<h{0}>{1}</h{0}>\n", level, escape(text)));
+            }
+            ReaderNode::Paragraph { text } => {
+                output.push_str(&format!("<p>{}</p>\n", escape(text)));
+            }
+            ReaderNode::Link { href, text } => {
+                output.push_str(&format!("<p><a href=\"{}\">{}</a></p>\n", escape(href), escape(text)));
+            }
+            ReaderNode::Image { src, alt } => {
+                output.push_str(&format!("<img src=\"{}\" alt=\"{}\">\n", escape(src), escape(alt)));
+            }
+        }
+    }
+
+    output
+}
+
+/// Escape text for use both as element content and inside double-quoted
+/// attribute values (`href`/`src`/`alt`), so a `"` in user-controlled text
+/// can't break out of the attribute it's embedded in
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_nav_and_script() {
+        let html = r#"<html><body><nav>Home</nav><script>alert('x')</script><p>Content</p></body></html>"#;
+        let reader = extract_reader_content(html);
+        assert!(!reader.contains("Home"));
+        assert!(!reader.contains("alert"));
+        assert!(reader.contains("Content"));
+    }
+
+    #[test]
+    fn test_extracts_heading_and_paragraph() {
+        let html = "<html><body><h2>Overview</h2><p>Some prose here.</p></body></html>";
+        let reader = extract_reader_content(html);
+        assert!(reader.contains("<h2>Overview</h2>"));
+        assert!(reader.contains("<p>Some prose here.</p>"));
+    }
+
+    #[test]
+    fn test_extracts_image() {
+        let html = r#"<html><body><img src="/pic.png" alt="diagram"></body></html>"#;
+        let reader = extract_reader_content(html);
+        assert!(reader.contains(r#"<img src="/pic.png" alt="diagram">"#));
+    }
+
+    #[test]
+    fn test_standalone_link_kept_nested_link_deduped() {
+        let html = r#"<html><body><p>See <a href="/ref">reference</a> for details.</p><a href="/standalone">Standalone</a></body></html>"#;
+        let reader = extract_reader_content(html);
+        assert!(reader.contains("See reference for details."));
+        assert!(reader.contains(r#"<a href="/standalone">Standalone</a>"#));
+        assert!(!reader.contains(r#"<a href="/ref">"#));
+    }
+}
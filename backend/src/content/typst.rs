@@ -1,17 +1,36 @@
 //! HTML to Typst conversion module
 
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use regex::Regex;
+use std::path::Path;
+
+use crate::citation::BibEntry;
+use crate::moodle::MoodleClient;
 
 // Use unique placeholders that won't be affected by escaping
 const MATH_START: &str = "___TYPST_MATH_";
 const MATH_END: &str = "_TYPST_MATH___";
 const HEADING_START: &str = "___TYPST_H";
 const HEADING_END: &str = "_TYPST_HEADING___";
+const IMAGE_START: &str = "___TYPST_IMG_";
+const IMAGE_END: &str = "_TYPST_IMAGE___";
+const CODE_START: &str = "___TYPST_CODE_";
+const CODE_END: &str = "_TYPST_CODE___";
 
 /// Convert cleaned HTML content to Typst markup
-pub fn html_to_typst(html: &str) -> String {
+///
+/// Non-math `<img>` elements are downloaded via `client` (using `token` for
+/// Moodle pluginfile URLs) and written into `assets_dir` so they survive as
+/// `#figure(image(...))` instead of being silently dropped with the rest of
+/// the tags.
+pub async fn html_to_typst(html: &str, client: &MoodleClient, token: &str, assets_dir: &Path) -> String {
     let mut result = html.to_string();
-    
+
+    // STEP 1: Extract and protect <pre>/<code> regions before anything else
+    // touches them (their entities are decoded inside `strip_inner_tags`)
+    let (result_with_code_placeholders, code_blocks) = extract_code_blocks(&result);
+    result = result_with_code_placeholders;
+
     // Decode HTML entities first
     result = result.replace("&amp;", "&");
     result = result.replace("&lt;", "<");
@@ -19,35 +38,282 @@ pub fn html_to_typst(html: &str) -> String {
     result = result.replace("&quot;", "\"");
     result = result.replace("&nbsp;", " ");
     result = result.replace("&#39;", "'");
-    
-    // STEP 1: Extract and protect math expressions before any processing
+
+    // STEP 2: Extract and protect <img> tags
+    let (result_with_img_placeholders, images) = extract_images(&result);
+    result = result_with_img_placeholders;
+
+    // STEP 3: Extract and protect math expressions before any processing
     let (result_with_placeholders, math_blocks) = extract_math(&result);
     result = result_with_placeholders;
-    
-    // STEP 2: Extract and convert headings
+
+    // STEP 4: Extract and convert headings
     result = extract_headings(&result);
-    
-    // STEP 3: Convert lists
+
+    // STEP 5: Convert lists
     result = convert_lists(&result);
-    
-    // STEP 4: Strip HTML tags
+
+    // STEP 6: Strip HTML tags
     result = strip_html_tags(&result);
-    
-    // STEP 5: Escape special characters in NON-MATH content
+
+    // STEP 7: Escape special characters in NON-MATH content
     result = escape_typst_content(&result);
-    
-    // STEP 6: Restore heading markers
+
+    // STEP 8: Restore heading markers
     result = restore_headings(&result);
-    
-    // STEP 7: Restore math expressions (converted to Typst format)
+
+    // STEP 9: Restore math expressions (converted to Typst format)
     result = restore_math(&result, &math_blocks);
-    
-    // STEP 8: Clean up whitespace
+
+    // STEP 10: Restore code blocks as Typst raw blocks (verbatim, never escaped)
+    result = restore_code_blocks(&result, &code_blocks);
+
+    // STEP 11: Download and embed images as Typst figures
+    result = embed_images(&result, &images, client, token, assets_dir).await;
+
+    // STEP 12: Clean up whitespace
     result = clean_whitespace(&result);
-    
+
+    result
+}
+
+/// A `<pre>`/`<code>` region captured out of the source HTML before tag-stripping
+struct CodeBlock {
+    lang: Option<String>,
+    code: String,
+    inline: bool,
+}
+
+/// Extract `<pre>`/`<code>` regions and replace with placeholders, to be
+/// restored as Typst raw blocks later (bypassing `escape_typst_content`)
+fn extract_code_blocks(html: &str) -> (String, Vec<CodeBlock>) {
+    let mut blocks: Vec<CodeBlock> = Vec::new();
+    let mut result = html.to_string();
+
+    // <pre><code class="language-xxx">...</code></pre>
+    if let Ok(re) = Regex::new(r#"(?si)<pre[^>]*>\s*<code([^>]*)>([\s\S]*?)</code>\s*</pre>"#) {
+        result = re.replace_all(&result, |caps: &regex::Captures| {
+            let lang = extract_lang_from_class(&caps[1]);
+            let code = strip_inner_tags(&caps[2]);
+            push_code_block(&mut blocks, lang, code, false)
+        }).to_string();
+    }
+
+    // Bare <pre>...</pre> with no nested <code> - still a block, no language info
+    if let Ok(re) = Regex::new(r#"(?si)<pre[^>]*>([\s\S]*?)</pre>"#) {
+        result = re.replace_all(&result, |caps: &regex::Captures| {
+            let code = strip_inner_tags(&caps[1]);
+            push_code_block(&mut blocks, None, code, false)
+        }).to_string();
+    }
+
+    // Remaining standalone inline <code>...</code>
+    if let Ok(re) = Regex::new(r#"(?si)<code([^>]*)>(.*?)</code>"#) {
+        result = re.replace_all(&result, |caps: &regex::Captures| {
+            let lang = extract_lang_from_class(&caps[1]);
+            let code = strip_inner_tags(&caps[2]);
+            push_code_block(&mut blocks, lang, code, true)
+        }).to_string();
+    }
+
+    (result, blocks)
+}
+
+fn push_code_block(blocks: &mut Vec<CodeBlock>, lang: Option<String>, code: String, inline: bool) -> String {
+    let idx = blocks.len();
+    blocks.push(CodeBlock { lang, code, inline });
+    format!("{}{}{}", CODE_START, idx, CODE_END)
+}
+
+/// Pull a `language-xxx`/`lang-xxx` class off a `<code>` tag's attributes
+fn extract_lang_from_class(attrs: &str) -> Option<String> {
+    Regex::new(r#"\b(?:language|lang)-([a-zA-Z0-9_+-]+)"#)
+        .ok()
+        .and_then(|re| re.captures(attrs))
+        .map(|c| c[1].to_string())
+}
+
+/// Strip nested tags (e.g. syntax-highlighting `<span>`s) without touching
+/// whitespace/newlines, then decode HTML entities so `&lt;` etc. come out verbatim
+fn strip_inner_tags(html: &str) -> String {
+    Regex::new(r"<[^>]+>")
+        .unwrap()
+        .replace_all(html, "")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&nbsp;", " ")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Restore code placeholders as Typst raw blocks/inline raw, verbatim
+fn restore_code_blocks(text: &str, blocks: &[CodeBlock]) -> String {
+    let mut result = text.to_string();
+
+    for (idx, block) in blocks.iter().enumerate() {
+        let placeholder = format!("{}{}{}", CODE_START, idx, CODE_END);
+        let escaped_placeholder = placeholder.replace("_", "\\_");
+
+        let code = block.code.trim_matches('\n');
+        let replacement = if block.inline {
+            let fence = typst_raw_fence(code, 1);
+            format!("{0}{1}{0}", fence, code.replace('\n', " "))
+        } else {
+            let fence = typst_raw_fence(code, 3);
+            let lang = block.lang.as_deref().unwrap_or("");
+            format!("\n{0}{1}\n{2}\n{0}\n", fence, lang, code)
+        };
+
+        result = result.replace(&escaped_placeholder, &replacement);
+        result = result.replace(&placeholder, &replacement);
+    }
+
     result
 }
 
+/// Pick a backtick fence at least `min_len` long that doesn't collide with any
+/// backtick run already present in `content`
+fn typst_raw_fence(content: &str, min_len: usize) -> String {
+    let mut len = min_len;
+    while content.contains(&"`".repeat(len)) {
+        len += 1;
+    }
+    "`".repeat(len)
+}
+
+/// An `<img>` reference captured out of the source HTML before tag-stripping
+struct ImageRef {
+    src: String,
+    alt: String,
+}
+
+/// Extract `<img>` tags and replace with placeholders, to be restored as Typst figures later
+fn extract_images(html: &str) -> (String, Vec<ImageRef>) {
+    let mut images: Vec<ImageRef> = Vec::new();
+
+    let Ok(re) = Regex::new(r#"(?i)<img\s+[^>]*src=["']([^"']+)["'][^>]*>"#) else {
+        return (html.to_string(), images);
+    };
+
+    let result = re.replace_all(html, |caps: &regex::Captures| {
+        let tag = &caps[0];
+        let src = caps[1].to_string();
+        let alt = Regex::new(r#"(?i)alt=["']([^"']*)["']"#)
+            .ok()
+            .and_then(|alt_re| alt_re.captures(tag).map(|c| c[1].to_string()))
+            .unwrap_or_default();
+
+        let idx = images.len();
+        images.push(ImageRef { src, alt });
+        format!("{}{}{}", IMAGE_START, idx, IMAGE_END)
+    }).to_string();
+
+    (result, images)
+}
+
+/// Download each captured image and swap its placeholder for a Typst `#figure`
+async fn embed_images(text: &str, images: &[ImageRef], client: &MoodleClient, token: &str, assets_dir: &Path) -> String {
+    let mut result = text.to_string();
+
+    for (idx, image) in images.iter().enumerate() {
+        let placeholder = format!("{}{}{}", IMAGE_START, idx, IMAGE_END);
+        let escaped_placeholder = placeholder.replace("_", "\\_");
+
+        let figure = match fetch_image_bytes(image, client, token).await {
+            Some((bytes, ext)) => match write_image_asset(assets_dir, idx, &ext, &bytes) {
+                Ok(path) => {
+                    let safe_path = path.replace('\\', "\\\\").replace('"', "\\\"");
+                    if image.alt.trim().is_empty() {
+                        format!("\n#figure(image(\"{}\"))\n", safe_path)
+                    } else {
+                        format!("\n#figure(image(\"{}\"), caption: [{}])\n", safe_path, escape_typst_content(&image.alt))
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to write embedded image asset for {}: {}", image.src, e);
+                    String::new()
+                }
+            },
+            None => String::new(),
+        };
+
+        result = result.replace(&escaped_placeholder, &figure);
+        result = result.replace(&placeholder, &figure);
+    }
+
+    result
+}
+
+/// Fetch raw bytes for an `<img src>`, handling both data URIs and remote (Moodle) URLs
+async fn fetch_image_bytes(image: &ImageRef, client: &MoodleClient, token: &str) -> Option<(Vec<u8>, String)> {
+    if let Some(data) = image.src.strip_prefix("data:") {
+        let (meta, payload) = data.split_once(',')?;
+        if !meta.contains("base64") {
+            return None;
+        }
+        let mime = meta.split(';').next().unwrap_or("image/png");
+        let bytes = STANDARD.decode(payload).ok()?;
+        return Some((bytes, extension_for_mime(mime)));
+    }
+
+    // An absolute URL pointing somewhere other than this Moodle instance would
+    // have the caller's live Moodle token appended and fetched server-side -
+    // full SSRF plus token exfiltration - so only ever follow our own host.
+    if image.src.starts_with("http") && !client.is_own_host(&image.src) {
+        tracing::warn!("Refusing to fetch non-Moodle image URL: {}", image.src);
+        return None;
+    }
+
+    match client.download_bytes(token, &image.src).await {
+        Ok((bytes, content_type)) => {
+            let ext = content_type
+                .as_deref()
+                .map(extension_for_mime)
+                .unwrap_or_else(|| extension_from_url(&image.src));
+            Some((bytes, ext))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to download embedded image {}: {}", image.src, e);
+            None
+        }
+    }
+}
+
+/// Map a MIME type to a Typst-friendly file extension
+fn extension_for_mime(mime: &str) -> String {
+    match mime.trim() {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/svg+xml" => "svg",
+        "image/webp" => "webp",
+        _ => "png",
+    }.to_string()
+}
+
+/// Guess an extension from a URL's path when no content-type is available
+fn extension_from_url(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    match path.rsplit('.').next() {
+        Some(ext) if ["jpg", "jpeg", "png", "gif", "svg", "webp"].contains(&ext.to_lowercase().as_str()) => {
+            ext.to_lowercase()
+        }
+        _ => "png".to_string(),
+    }
+}
+
+/// Write a downloaded image into the export's assets directory, returning a
+/// path relative to the `.typ` file's directory (`assets_dir`'s parent) -
+/// Typst resolves a leading `/` against its project root, not the real
+/// filesystem root, so an absolute path here would never resolve at compile time
+fn write_image_asset(assets_dir: &Path, idx: usize, ext: &str, bytes: &[u8]) -> std::io::Result<String> {
+    std::fs::create_dir_all(assets_dir)?;
+    let filename = format!("image-{}.{}", idx, ext);
+    std::fs::write(assets_dir.join(&filename), bytes)?;
+    Ok(format!("assets/{}", filename))
+}
+
 /// Extract math expressions and replace with placeholders
 fn extract_math(html: &str) -> (String, Vec<(bool, String)>) {
     let mut result = html.to_string();
@@ -240,7 +506,25 @@ fn escape_typst_content(text: &str) -> String {
                 continue;
             }
         }
-        
+
+        if remaining.starts_with(IMAGE_START) {
+            if let Some(end_pos) = remaining.find(IMAGE_END) {
+                let placeholder = &remaining[..end_pos + IMAGE_END.len()];
+                result.push_str(placeholder);
+                i += placeholder.len();
+                continue;
+            }
+        }
+
+        if remaining.starts_with(CODE_START) {
+            if let Some(end_pos) = remaining.find(CODE_END) {
+                let placeholder = &remaining[..end_pos + CODE_END.len()];
+                result.push_str(placeholder);
+                i += placeholder.len();
+                continue;
+            }
+        }
+
         let c = chars[i];
         match c {
             '\\' => result.push_str("\\\\"),
@@ -294,7 +578,12 @@ fn clean_whitespace(text: &str) -> String {
 }
 
 /// Generate a complete Typst document with template
-pub fn generate_typst_document(title: &str, sections: Vec<(String, String)>) -> String {
+///
+/// When `bibliography` is non-empty, it is written as `refs.bib` inside
+/// `work_dir` and a `#bibliography(...)` call is appended to the document so
+/// any `[@key]` markers left in section content resolve. Failure to write the
+/// file is logged and the document is still produced, without citations.
+pub fn generate_typst_document(title: &str, sections: Vec<(String, String)>, bibliography: &[BibEntry], work_dir: &Path) -> String {
     let mut doc = String::new();
     
     let safe_title = title.replace("\\", "\\\\").replace("\"", "\\\"");
@@ -344,7 +633,14 @@ pub fn generate_typst_document(title: &str, sections: Vec<(String, String)>) ->
         doc.push_str(&format!("\n\n= {}\n\n", safe_section));
         doc.push_str(&content);
     }
-    
+
+    if !bibliography.is_empty() {
+        match crate::citation::write_bibtex(work_dir, bibliography) {
+            Ok(filename) => doc.push_str(&format!("\n\n#bibliography(\"{}\")\n", filename)),
+            Err(e) => tracing::warn!("Failed to write bibliography for Typst export: {}", e),
+        }
+    }
+
     doc
 }
 
@@ -0,0 +1,54 @@
+//! Overlapping-chunk text splitter
+//!
+//! Token counts are approximated by whitespace-separated words, which is
+//! enough to keep each chunk near its target length without pulling in a
+//! real tokenizer.
+
+/// Split `text` into overlapping chunks of ~`chunk_tokens` words, each
+/// starting `chunk_tokens - overlap_tokens` words after the previous one
+pub fn chunk_text(text: &str, chunk_tokens: usize, overlap_tokens: usize) -> Vec<String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.is_empty() {
+        return Vec::new();
+    }
+
+    let stride = chunk_tokens.saturating_sub(overlap_tokens).max(1);
+    let mut chunks = Vec::new();
+    let mut start = 0;
+
+    while start < words.len() {
+        let end = (start + chunk_tokens).min(words.len());
+        chunks.push(words[start..end].join(" "));
+        if end == words.len() {
+            break;
+        }
+        start += stride;
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_text_overlap() {
+        let text = (0..1200).map(|i| i.to_string()).collect::<Vec<_>>().join(" ");
+        let chunks = chunk_text(&text, 500, 50);
+        assert!(chunks.len() >= 3);
+        assert!(chunks[0].ends_with("499"));
+        assert!(chunks[1].starts_with("450"));
+    }
+
+    #[test]
+    fn test_chunk_text_short_input() {
+        let chunks = chunk_text("just a few words", 500, 50);
+        assert_eq!(chunks.len(), 1);
+    }
+
+    #[test]
+    fn test_chunk_text_empty_input() {
+        assert!(chunk_text("   ", 500, 50).is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+//! Pluggable embedding backend
+//!
+//! `HashingEmbedder` is the default: a deterministic local embedding (no
+//! model weights or network access required) that's good enough to rank
+//! chunks of course content against each other. A remote API-backed
+//! `Embedder` can be swapped in behind the same trait without touching the
+//! indexing or search code.
+
+use crate::error::Result;
+
+/// Fixed dimensionality every `Embedder` implementation must produce
+pub const EMBEDDING_DIM: usize = 256;
+
+#[async_trait::async_trait]
+pub trait Embedder: Send + Sync {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>>;
+}
+
+/// Local default: hashes each word into a dimension bucket and L2-normalizes
+/// the result - a bag-of-words embedding that needs no model or network call
+pub struct HashingEmbedder;
+
+#[async_trait::async_trait]
+impl Embedder for HashingEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut vector = vec![0f32; EMBEDDING_DIM];
+
+        for word in text.split_whitespace() {
+            vector[hash_to_bucket(&word.to_lowercase())] += 1.0;
+        }
+
+        normalize(&mut vector);
+        Ok(vector)
+    }
+}
+
+fn hash_to_bucket(word: &str) -> usize {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    word.hash(&mut hasher);
+    (hasher.finish() as usize) % EMBEDDING_DIM
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for v in vector.iter_mut() {
+            *v /= norm;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::embeddings::store::cosine_similarity;
+
+    #[tokio::test]
+    async fn test_hashing_embedder_dimension() {
+        let vector = HashingEmbedder.embed("hello world").await.unwrap();
+        assert_eq!(vector.len(), EMBEDDING_DIM);
+    }
+
+    #[tokio::test]
+    async fn test_hashing_embedder_similar_text_ranks_closer() {
+        let a = HashingEmbedder.embed("rust async programming").await.unwrap();
+        let b = HashingEmbedder.embed("rust async programming tutorial").await.unwrap();
+        let c = HashingEmbedder.embed("baking sourdough bread").await.unwrap();
+
+        assert!(cosine_similarity(&a, &b) > cosine_similarity(&a, &c));
+    }
+}
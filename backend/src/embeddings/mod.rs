@@ -0,0 +1,100 @@
+//! Semantic search over indexed course content
+//!
+//! Section summaries and activity names surfaced by `get_course_contents`
+//! are split into overlapping chunks, embedded, and stored in a
+//! `VectorStore` so `/content/search` can rank them by cosine similarity
+//! against a query embedding instead of requiring the user to scroll every
+//! section.
+
+mod chunk;
+mod embedder;
+mod store;
+
+pub use chunk::chunk_text;
+pub use embedder::{Embedder, HashingEmbedder, EMBEDDING_DIM};
+pub use store::{IndexedChunk, ScoredChunk, VectorStore, InMemoryVectorStore, INDEX};
+
+use regex::Regex;
+use std::time::Duration;
+
+use crate::cache::{self, MemoryCache};
+use crate::error::Result;
+use crate::moodle::CourseSection;
+
+const CHUNK_TOKENS: usize = 500;
+const CHUNK_OVERLAP_TOKENS: usize = 50;
+const EMBEDDING_CACHE_TTL: Duration = Duration::from_secs(86400);
+
+/// Embed `text`, memoizing the vector in the cache backend keyed by a hash of the content
+pub async fn embed_cached(embedder: &dyn Embedder, text: &str) -> Result<Vec<f32>> {
+    let cache_key = format!("embedding:{}", MemoryCache::url_hash(text));
+
+    if let Some(cached) = cache::get(&cache_key).await {
+        if let Ok(vector) = serde_json::from_str::<Vec<f32>>(&cached) {
+            return Ok(vector);
+        }
+    }
+
+    let vector = embedder.embed(text).await?;
+    if let Ok(serialized) = serde_json::to_string(&vector) {
+        cache::set(&cache_key, &serialized, Some(EMBEDDING_CACHE_TTL)).await;
+    }
+
+    Ok(vector)
+}
+
+/// Re-index a course's sections for semantic search, replacing any prior index for this course
+pub async fn index_course(course_id: i64, sections: &[CourseSection]) {
+    let embedder = HashingEmbedder;
+    INDEX.clear_course(course_id).await;
+
+    for section in sections {
+        let mut text = section.summary.as_deref().map(html_to_plain_text).unwrap_or_default();
+        for module in &section.modules {
+            text.push_str(". ");
+            text.push_str(&module.name);
+        }
+
+        if let Err(e) = index_text(&embedder, course_id, section, &text).await {
+            tracing::warn!("Failed to index section {} of course {}: {}", section.id, course_id, e);
+        }
+    }
+}
+
+/// Split `text` into overlapping chunks and embed+index each one under a section's refs
+async fn index_text(embedder: &dyn Embedder, course_id: i64, section: &CourseSection, text: &str) -> Result<()> {
+    for piece in chunk_text(text, CHUNK_TOKENS, CHUNK_OVERLAP_TOKENS) {
+        if piece.trim().is_empty() {
+            continue;
+        }
+
+        let vector = embed_cached(embedder, &piece).await?;
+        INDEX.upsert(IndexedChunk {
+            course_id,
+            section_id: section.id,
+            section_name: section.name.clone(),
+            module_id: None,
+            module_name: None,
+            text: piece,
+            vector,
+        }).await;
+    }
+
+    Ok(())
+}
+
+/// Strip tags and decode a handful of common entities, for text that's only used to build search chunks
+fn html_to_plain_text(html: &str) -> String {
+    let stripped = Regex::new(r"<[^>]+>").unwrap().replace_all(html, " ").to_string();
+
+    stripped
+        .replace("&amp;", "&")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&nbsp;", " ")
+        .replace("&#39;", "'")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
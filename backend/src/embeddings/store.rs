@@ -0,0 +1,139 @@
+//! Vector index for embedded content chunks
+//!
+//! `InMemoryVectorStore` is the default `VectorStore`. A Postgres/pgvector
+//! implementation can be added behind the same trait for persistence across
+//! restarts without touching the indexing or search code - the same way
+//! `CacheBackend` is implemented by both `MemoryCache` and `RedisCache`.
+
+use std::sync::RwLock;
+
+/// A single embedded chunk of course content, tagged with where it came from
+#[derive(Debug, Clone)]
+pub struct IndexedChunk {
+    pub course_id: i64,
+    pub section_id: i64,
+    pub section_name: String,
+    pub module_id: Option<i64>,
+    pub module_name: Option<String>,
+    pub text: String,
+    pub vector: Vec<f32>,
+}
+
+/// An indexed chunk plus its cosine similarity to a query
+#[derive(Debug, Clone)]
+pub struct ScoredChunk {
+    pub chunk: IndexedChunk,
+    pub score: f32,
+}
+
+#[async_trait::async_trait]
+pub trait VectorStore: Send + Sync {
+    async fn upsert(&self, chunk: IndexedChunk);
+    async fn search(&self, course_id: i64, query_vector: &[f32], top_k: usize) -> Vec<ScoredChunk>;
+    async fn clear_course(&self, course_id: i64);
+}
+
+/// In-process vector store - fine for a single backend instance, lost on restart
+#[derive(Default)]
+pub struct InMemoryVectorStore {
+    chunks: RwLock<Vec<IndexedChunk>>,
+}
+
+impl InMemoryVectorStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait::async_trait]
+impl VectorStore for InMemoryVectorStore {
+    async fn upsert(&self, chunk: IndexedChunk) {
+        if let Ok(mut chunks) = self.chunks.write() {
+            chunks.push(chunk);
+        }
+    }
+
+    async fn search(&self, course_id: i64, query_vector: &[f32], top_k: usize) -> Vec<ScoredChunk> {
+        let Ok(chunks) = self.chunks.read() else {
+            return Vec::new();
+        };
+
+        let mut scored: Vec<ScoredChunk> = chunks
+            .iter()
+            .filter(|c| c.course_id == course_id)
+            .map(|c| ScoredChunk {
+                chunk: c.clone(),
+                score: cosine_similarity(query_vector, &c.vector),
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(top_k);
+        scored
+    }
+
+    async fn clear_course(&self, course_id: i64) {
+        if let Ok(mut chunks) = self.chunks.write() {
+            chunks.retain(|c| c.course_id != course_id);
+        }
+    }
+}
+
+/// Cosine similarity `a·b / (|a||b|)`, 0.0 if either vector is zero-length
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+lazy_static::lazy_static! {
+    /// Global content index - swap for a Postgres/pgvector-backed `VectorStore` for persistence
+    pub static ref INDEX: InMemoryVectorStore = InMemoryVectorStore::new();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(course_id: i64, text: &str, vector: Vec<f32>) -> IndexedChunk {
+        IndexedChunk {
+            course_id,
+            section_id: 1,
+            section_name: "Section".to_string(),
+            module_id: None,
+            module_name: None,
+            text: text.to_string(),
+            vector,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_by_similarity_and_filters_course() {
+        let store = InMemoryVectorStore::new();
+        store.upsert(chunk(1, "match", vec![1.0, 0.0])).await;
+        store.upsert(chunk(1, "other", vec![0.0, 1.0])).await;
+        store.upsert(chunk(2, "wrong course", vec![1.0, 0.0])).await;
+
+        let results = store.search(1, &[1.0, 0.0], 5).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].chunk.text, "match");
+    }
+
+    #[tokio::test]
+    async fn test_clear_course_removes_only_that_course() {
+        let store = InMemoryVectorStore::new();
+        store.upsert(chunk(1, "a", vec![1.0])).await;
+        store.upsert(chunk(2, "b", vec![1.0])).await;
+
+        store.clear_course(1).await;
+
+        assert_eq!(store.search(1, &[1.0], 5).await.len(), 0);
+        assert_eq!(store.search(2, &[1.0], 5).await.len(), 1);
+    }
+}
@@ -0,0 +1,231 @@
+//! Assembles cleaned Moodle pages into a downloadable EPUB
+//!
+//! One XHTML section per chapter, a table of contents built from the
+//! `<h2>`/`<h3>` headings the cleaner already preserves, and embedded images
+//! downloaded through the caller's Moodle token - a protected
+//! `pluginfile.php` URL left as-is would be unreachable from an offline
+//! EPUB reader.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use epub_builder::{EpubBuilder, EpubContent, ReferenceType, TocElement, ZipLibrary};
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use super::types::{BookMeta, CleanedChapter};
+use crate::content::rewrite_image_srcs;
+use crate::error::AppError;
+use crate::moodle::MoodleClient;
+
+const DEFAULT_STYLESHEET: &str = r#"
+body { font-family: serif; line-height: 1.5; margin: 1em; }
+h1, h2, h3 { font-family: sans-serif; }
+img { max-width: 100%; }
+"#;
+
+/// Build a complete EPUB from pre-cleaned chapters, downloading and embedding
+/// any protected Moodle images found in their HTML
+pub async fn build_epub(
+    chapters: Vec<CleanedChapter>,
+    meta: BookMeta,
+    client: &MoodleClient,
+    token: &str,
+) -> Result<Vec<u8>, AppError> {
+    let zip = ZipLibrary::new().map_err(|e| AppError::Internal(format!("Failed to create EPUB archive: {}", e)))?;
+    let mut builder = EpubBuilder::new(zip)
+        .map_err(|e| AppError::Internal(format!("Failed to create EPUB builder: {}", e)))?;
+
+    builder
+        .metadata("title", &meta.title)
+        .map_err(|e| AppError::Internal(format!("Failed to set EPUB title: {}", e)))?;
+    if let Some(author) = &meta.author {
+        builder
+            .metadata("author", author)
+            .map_err(|e| AppError::Internal(format!("Failed to set EPUB author: {}", e)))?;
+    }
+    builder
+        .metadata("lang", meta.language.as_deref().unwrap_or("en"))
+        .map_err(|e| AppError::Internal(format!("Failed to set EPUB language: {}", e)))?;
+    builder
+        .stylesheet(DEFAULT_STYLESHEET.as_bytes())
+        .map_err(|e| AppError::Internal(format!("Failed to attach EPUB stylesheet: {}", e)))?;
+
+    let mut image_idx = 0usize;
+
+    for (chapter_idx, chapter) in chapters.iter().enumerate() {
+        let filename = format!("chapter_{}.xhtml", chapter_idx + 1);
+
+        let (with_images, resources) = embed_images(&chapter.html, &mut image_idx, client, token).await;
+        for (path, bytes, mime) in resources {
+            builder
+                .add_resource(&path, Cursor::new(bytes), &mime)
+                .map_err(|e| AppError::Internal(format!("Failed to embed image {}: {}", path, e)))?;
+        }
+
+        let (tagged, toc_entries) = tag_headings(&with_images);
+        let xhtml = wrap_xhtml(&chapter.title, &tagged);
+
+        let mut content = EpubContent::new(filename.clone(), xhtml.as_bytes())
+            .title(chapter.title.clone())
+            .reftype(ReferenceType::Text);
+        for (heading, anchor) in toc_entries {
+            content = content.child(TocElement::new(format!("{}#{}", filename, anchor), heading));
+        }
+
+        builder
+            .add_content(content)
+            .map_err(|e| AppError::Internal(format!("Failed to add chapter {}: {}", chapter_idx + 1, e)))?;
+    }
+
+    builder.inline_toc();
+
+    let mut output = Vec::new();
+    builder
+        .generate(&mut output)
+        .map_err(|e| AppError::Internal(format!("Failed to generate EPUB: {}", e)))?;
+
+    Ok(output)
+}
+
+/// Download each Moodle-protected `<img src>` in `html` and rewrite it to
+/// point at an embedded resource path, returning the rewritten HTML plus the
+/// `(resource_path, bytes, mime)` triples to add to the EPUB
+async fn embed_images(
+    html: &str,
+    image_idx: &mut usize,
+    client: &MoodleClient,
+    token: &str,
+) -> (String, Vec<(String, Vec<u8>, String)>) {
+    let mut mapping: HashMap<String, String> = HashMap::new();
+    let mut resources = Vec::new();
+
+    for url in extract_image_srcs(html) {
+        if mapping.contains_key(&url) {
+            continue;
+        }
+
+        let fetched = fetch_image_bytes(&url, client, token).await;
+        if let Some((bytes, mime)) = fetched {
+            *image_idx += 1;
+            let path = format!("images/image-{}.{}", image_idx, extension_for_mime(&mime));
+            mapping.insert(url, path.clone());
+            resources.push((path, bytes, mime));
+        }
+    }
+
+    let xhtml = rewrite_image_srcs(html, |url| mapping.get(url).cloned());
+    (xhtml, resources)
+}
+
+/// Fetch raw bytes for an `<img src>`, handling both data URIs and protected Moodle URLs
+async fn fetch_image_bytes(url: &str, client: &MoodleClient, token: &str) -> Option<(Vec<u8>, String)> {
+    if let Some(data) = url.strip_prefix("data:") {
+        let (meta, payload) = data.split_once(',')?;
+        if !meta.contains("base64") {
+            return None;
+        }
+        let mime = meta.split(';').next().unwrap_or("image/png").to_string();
+        let bytes = STANDARD.decode(payload).ok()?;
+        return Some((bytes, mime));
+    }
+
+    // A `contains` check on the raw URL is not a host check - e.g.
+    // `http://evil.com/?x=https://mylms.vossie.net/` contains the configured
+    // Moodle URL as a substring and would pass. Parse and compare hosts.
+    if url.starts_with("http") && !client.is_own_host(url) {
+        return None;
+    }
+
+    match client.download_bytes(token, url).await {
+        Ok((bytes, content_type)) => Some((bytes, content_type.unwrap_or_else(|| "image/png".to_string()))),
+        Err(e) => {
+            tracing::warn!("Failed to download EPUB image {}: {}", url, e);
+            None
+        }
+    }
+}
+
+fn extract_image_srcs(html: &str) -> Vec<String> {
+    let Ok(re) = Regex::new(r#"(?i)<img[^>]+src=["']([^"']+)["']"#) else {
+        return Vec::new();
+    };
+    re.captures_iter(html).map(|c| c[1].to_string()).collect()
+}
+
+/// Map a MIME type (optionally with a `; charset=...` suffix) to a file extension
+fn extension_for_mime(mime: &str) -> &'static str {
+    match mime.split(';').next().unwrap_or(mime).trim() {
+        "image/jpeg" | "image/jpg" => "jpg",
+        "image/gif" => "gif",
+        "image/svg+xml" => "svg",
+        "image/webp" => "webp",
+        _ => "png",
+    }
+}
+
+/// Give every `<h2>`/`<h3>` heading a stable anchor id (a heading that
+/// already has an `id` keeps it) and return its `(text, anchor)` pairs for
+/// the table of contents
+fn tag_headings(html: &str) -> (String, Vec<(String, String)>) {
+    let mut result = html.to_string();
+    let mut entries = Vec::new();
+    let mut idx = 0usize;
+
+    for tag in ["h2", "h3"] {
+        let Ok(re) = Regex::new(&format!(r#"(?si)<{tag}([^>]*)>(.*?)</{tag}>"#, tag = tag)) else {
+            continue;
+        };
+
+        result = re
+            .replace_all(&result, |caps: &regex::Captures| {
+                let attrs = &caps[1];
+                let inner = &caps[2];
+                let text = strip_tags(inner).trim().to_string();
+                if text.is_empty() {
+                    return caps[0].to_string();
+                }
+
+                idx += 1;
+                let existing_id = Regex::new(r#"(?:^|\s)id=["']([^"']*)["']"#)
+                    .ok()
+                    .and_then(|id_re| id_re.captures(attrs).map(|c| c[1].to_string()));
+
+                if let Some(id) = existing_id {
+                    entries.push((text, id));
+                    caps[0].to_string()
+                } else {
+                    let anchor = format!("toc-{}", idx);
+                    entries.push((text, anchor.clone()));
+                    format!("<{tag} id=\"{anchor}\"{attrs}>{inner}</{tag}>", tag = tag, anchor = anchor, attrs = attrs, inner = inner)
+                }
+            })
+            .to_string();
+    }
+
+    (result, entries)
+}
+
+fn strip_tags(html: &str) -> String {
+    Regex::new(r"<[^>]+>").unwrap().replace_all(html, "").to_string()
+}
+
+fn wrap_xhtml(title: &str, body: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head><title>{}</title></head>
+<body>
+<h1>{}</h1>
+{}
+</body>
+</html>"#,
+        escape_xml(title),
+        escape_xml(title),
+        body
+    )
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,11 @@
+//! EPUB export module
+//!
+//! Packages cleaned Moodle pages into a single downloadable EPUB, so course
+//! content the `content` module already sanitizes can be read offline
+//! instead of only inside the Moodle web view.
+
+mod generator;
+mod types;
+
+pub use generator::build_epub;
+pub use types::{BookMeta, CleanedChapter};
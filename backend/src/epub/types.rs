@@ -0,0 +1,23 @@
+//! Types consumed by the EPUB generator
+
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+/// A single cleaned HTML page to include as one EPUB chapter
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CleanedChapter {
+    pub title: String,
+    /// Already-cleaned HTML (see `content::clean_html_with_token`)
+    pub html: String,
+}
+
+/// Book-level metadata for the generated EPUB
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct BookMeta {
+    pub title: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    /// BCP-47 language tag, e.g. "en"
+    #[serde(default)]
+    pub language: Option<String>,
+}
@@ -5,8 +5,17 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use serde::Serialize;
 use serde_json::json;
 use thiserror::Error;
+use utoipa::ToSchema;
+
+/// Documented shape of an error response, for the OpenAPI spec
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: bool,
+    pub message: String,
+}
 
 /// Application error types
 #[derive(Error, Debug)]
@@ -17,6 +26,9 @@ pub enum AppError {
     #[error("Invalid token: {0}")]
     InvalidToken(String),
 
+    #[error("Invalid credentials: {0}")]
+    InvalidCredentials(String),
+
     #[error("Moodle API error: {0}")]
     MoodleApi(String),
 
@@ -31,6 +43,9 @@ pub enum AppError {
 
     #[error("Cache error: {0}")]
     Cache(String),
+
+    #[error("Content too large: exceeds {0} byte limit")]
+    ContentTooLarge(usize),
 }
 
 impl IntoResponse for AppError {
@@ -38,11 +53,13 @@ impl IntoResponse for AppError {
         let (status, message) = match &self {
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, self.to_string()),
             AppError::InvalidToken(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
+            AppError::InvalidCredentials(_) => (StatusCode::UNAUTHORIZED, self.to_string()),
             AppError::MoodleApi(_) => (StatusCode::BAD_GATEWAY, self.to_string()),
             AppError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
             AppError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
             AppError::Request(_) => (StatusCode::BAD_GATEWAY, self.to_string()),
             AppError::Cache(_) => (StatusCode::INTERNAL_SERVER_ERROR, self.to_string()),
+            AppError::ContentTooLarge(_) => (StatusCode::PAYLOAD_TOO_LARGE, self.to_string()),
         };
 
         tracing::error!("API Error: {}", message);
@@ -1,97 +1,148 @@
 //! LibGen API client wrapper
-//! 
-//! Uses libgen-rs crate to search and download books from Library Genesis
+//!
+//! Libgen doesn't expose a stable API, so this talks to the same
+//! `search.php`/`book/index.php` pages a browser would, scraping the result
+//! table and the per-book download link with `scraper`. Mirrors come and go,
+//! so every request tries an ordered list of hosts and returns the first one
+//! that connects and responds within a per-mirror timeout.
+
+use scraper::{ElementRef, Html, Selector};
+use std::time::Duration;
+use tokio::time::timeout;
+use tracing::{debug, info, warn};
 
 use crate::error::AppError;
 use super::types::{Book, SearchResult};
-use tracing::{debug, info, error};
+
+/// Mirrors tried in order; libgen domains rotate in and out of availability
+const DEFAULT_MIRRORS: &[&str] = &["libgen.is", "libgen.rs", "libgen.st"];
+const MIRROR_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// LibGen client for searching and downloading books
 pub struct LibGenClient {
-    // We'll implement our own HTTP-based search since libgen-rs API is complex
     client: reqwest::Client,
+    mirrors: Vec<String>,
 }
 
 impl LibGenClient {
-    /// Create a new LibGen client
+    /// Create a client using the default mirror list
     pub fn new() -> Self {
+        Self::with_mirrors(DEFAULT_MIRRORS.iter().map(|s| s.to_string()).collect())
+    }
+
+    /// Create a client that tries `mirrors` in order
+    pub fn with_mirrors(mirrors: Vec<String>) -> Self {
         Self {
             client: reqwest::Client::new(),
+            mirrors,
         }
     }
 
-    /// Search for books by title/author using LibGen's JSON API
+    /// Search for books by title/author, scraping the first mirror that responds
     pub async fn search(&self, query: &str) -> Result<SearchResult, AppError> {
         info!("Searching LibGen for: {}", query);
-        
-        // Use LibGen's API directly for more reliable results
-        // The JSON API endpoint returns book data in JSON format
-        let search_url = format!(
-            "https://libgen.is/search.php?req={}&res=25&view=simple&phrase=1&column=def",
-            urlencoding::encode(query)
-        );
-        
-        debug!("Search URL: {}", search_url);
-        
-        // For now, return empty results - we'll implement the actual search
-        // when we have mirrors configured
-        Err(AppError::Internal("LibGen search not yet fully implemented - needs mirror configuration".to_string()))
-    }
 
-    /// Search using libgen-rs internal API (if available)
-    #[allow(dead_code)]
-    async fn search_via_crate(&self, query: &str) -> Result<SearchResult, AppError> {
-        // The libgen-rs crate requires mirrors.json configuration
-        // For now, we'll note this as a TODO
-        info!("LibGen crate search for: {}", query);
-        
-        // Return placeholder - actual implementation depends on mirrors setup
+        let encoded = urlencoding::encode(query).into_owned();
+        let (host, response) = self
+            .fetch_from_mirrors(|host| {
+                format!(
+                    "https://{}/search.php?req={}&res=25&view=simple&phrase=1&column=def",
+                    host, encoded
+                )
+            })
+            .await?;
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to read LibGen response from {}: {}", host, e)))?;
+
+        let books = parse_search_results(&body);
+        info!("LibGen search via {} returned {} results", host, books.len());
+
         Ok(SearchResult {
             query: query.to_string(),
-            books: vec![],
-            total: 0,
+            total: books.len(),
+            books,
         })
     }
 
-    /// Get download URL for a book by MD5 hash
+    /// Resolve the real download link for a book from its MD5 page
     pub async fn get_download_url(&self, md5: &str) -> Result<String, AppError> {
         info!("Getting download URL for MD5: {}", md5);
-        
-        // Common LibGen download mirrors
-        let download_url = format!(
-            "https://libgen.is/get.php?md5={}",
-            md5
-        );
-        
-        Ok(download_url)
+
+        let md5 = md5.to_lowercase();
+        let (host, response) = self
+            .fetch_from_mirrors(|host| format!("https://{}/book/index.php?md5={}", host, md5))
+            .await?;
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to read LibGen book page from {}: {}", host, e)))?;
+
+        parse_download_link(&body, &host).ok_or_else(|| AppError::NotFound(format!("No download link found for MD5 {}", md5)))
     }
-    
+
     /// Download a book and return the bytes
     pub async fn download_book(&self, md5: &str) -> Result<Vec<u8>, AppError> {
         let download_url = self.get_download_url(md5).await?;
-        
+
         info!("Downloading book from: {}", download_url);
-        
-        let response = self.client
+
+        let response = self
+            .client
             .get(&download_url)
             .send()
             .await
             .map_err(|e| AppError::Internal(format!("Download failed: {}", e)))?;
-        
+
         if !response.status().is_success() {
             return Err(AppError::Internal(format!(
                 "Download failed with status: {}",
                 response.status()
             )));
         }
-        
+
         let bytes = response
             .bytes()
             .await
             .map_err(|e| AppError::Internal(format!("Failed to read bytes: {}", e)))?;
-        
+
         Ok(bytes.to_vec())
     }
+
+    /// Try `build_url` against each mirror in order, returning the first mirror
+    /// whose request connects and responds with a 2xx status within the per-mirror timeout
+    async fn fetch_from_mirrors(
+        &self,
+        build_url: impl Fn(&str) -> String,
+    ) -> Result<(String, reqwest::Response), AppError> {
+        let mut last_error = "no mirrors configured".to_string();
+
+        for host in &self.mirrors {
+            let url = build_url(host);
+            debug!("Trying LibGen mirror {}: {}", host, url);
+
+            match timeout(MIRROR_TIMEOUT, self.client.get(&url).send()).await {
+                Ok(Ok(response)) if response.status().is_success() => return Ok((host.clone(), response)),
+                Ok(Ok(response)) => {
+                    warn!("LibGen mirror {} returned {}", host, response.status());
+                    last_error = format!("{}: HTTP {}", host, response.status());
+                }
+                Ok(Err(e)) => {
+                    warn!("LibGen mirror {} request failed: {}", host, e);
+                    last_error = format!("{}: {}", host, e);
+                }
+                Err(_) => {
+                    warn!("LibGen mirror {} timed out", host);
+                    last_error = format!("{}: timed out", host);
+                }
+            }
+        }
+
+        Err(AppError::Internal(format!("All LibGen mirrors failed: {}", last_error)))
+    }
 }
 
 impl Default for LibGenClient {
@@ -99,3 +150,86 @@ impl Default for LibGenClient {
         Self::new()
     }
 }
+
+/// Parse a `search.php?view=simple` results table into `Book`s
+///
+/// Columns: ID, Author(s), Title, Publisher, Year, Language, Pages, Size, Extension, Mirrors, Edit
+fn parse_search_results(html: &str) -> Vec<Book> {
+    let document = Html::parse_document(html);
+    let (Ok(row_selector), Ok(cell_selector), Ok(link_selector)) = (
+        Selector::parse("table#tablelibgen > tbody > tr"),
+        Selector::parse("td"),
+        Selector::parse("a"),
+    ) else {
+        return Vec::new();
+    };
+
+    document
+        .select(&row_selector)
+        .filter_map(|row| {
+            let cells: Vec<_> = row.select(&cell_selector).collect();
+            if cells.len() < 9 {
+                return None;
+            }
+
+            let title_cell = &cells[2];
+            let title = cell_text(title_cell);
+            let md5 = title_cell
+                .select(&link_selector)
+                .find_map(|a| a.value().attr("href"))
+                .and_then(|href| href.split_once("md5="))
+                .map(|(_, rest)| rest.split(['&', '#']).next().unwrap_or(rest).to_lowercase())?;
+
+            if title.is_empty() || md5.is_empty() {
+                return None;
+            }
+
+            Some(Book {
+                id: cell_text(&cells[0]),
+                title,
+                author: cell_text(&cells[1]),
+                year: non_empty(cell_text(&cells[4])),
+                extension: cell_text(&cells[8]),
+                size: cell_text(&cells[7]),
+                md5,
+                pages: non_empty(cell_text(&cells[6])),
+                language: non_empty(cell_text(&cells[5])),
+                publisher: non_empty(cell_text(&cells[3])),
+                download_url: None,
+            })
+        })
+        .collect()
+}
+
+/// Pull the "GET" download link off a LibGen book page, resolving it against `host` if relative
+fn parse_download_link(html: &str, host: &str) -> Option<String> {
+    let document = Html::parse_document(html);
+    let link_selector = Selector::parse("a").ok()?;
+
+    let href = document.select(&link_selector).find_map(|a| {
+        let text: String = a.text().collect();
+        if text.trim().eq_ignore_ascii_case("GET") {
+            a.value().attr("href").map(|s| s.to_string())
+        } else {
+            None
+        }
+    })?;
+
+    if href.starts_with("http") {
+        Some(href)
+    } else {
+        Some(format!("https://{}/{}", host, href.trim_start_matches('/')))
+    }
+}
+
+fn cell_text(cell: &ElementRef) -> String {
+    cell.text().collect::<Vec<_>>().join(" ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn non_empty(s: String) -> Option<String> {
+    if s.is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
@@ -0,0 +1,191 @@
+//! Prescribed-reading citation extraction
+//!
+//! Scans course HTML for prescribed/required/recommended reading sections -
+//! the same `prescribed-reading`/`box`/`generalbox` containers `content::cleaner`
+//! treats as boilerplate - and pulls citation-shaped lines out of them into
+//! `PrescribedBook` records.
+
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashSet;
+
+use super::types::PrescribedBook;
+
+/// Container classes known to hold a prescribed-reading block
+const READING_CONTAINER_CLASSES: &[&str] = &["prescribed-reading", "box", "generalbox"];
+
+/// Heading text identifying a prescribed-reading section
+fn is_reading_heading(text: &str) -> bool {
+    Regex::new(r"(?i)prescribed|required|recommended reading")
+        .map(|re| re.is_match(text))
+        .unwrap_or(false)
+}
+
+/// Extract `PrescribedBook` citations from raw course HTML
+pub fn detect_prescribed_books(html: &str) -> Vec<PrescribedBook> {
+    let document = Html::parse_document(html);
+
+    let mut lines = container_lines(&document);
+    lines.extend(heading_lines(&document));
+
+    let mut books = Vec::new();
+    let mut seen = HashSet::new();
+    for line in lines {
+        if let Some(book) = parse_citation_line(&line) {
+            if seen.insert((book.title.to_lowercase(), book.chapter.clone())) {
+                books.push(book);
+            }
+        }
+    }
+
+    books
+}
+
+/// Candidate lines from `.prescribed-reading`/`.box`/`.generalbox` containers
+fn container_lines(document: &Html) -> Vec<String> {
+    let mut lines = Vec::new();
+    for class in READING_CONTAINER_CLASSES {
+        let Ok(selector) = Selector::parse(&format!(".{}", class)) else {
+            continue;
+        };
+        for element in document.select(&selector) {
+            lines.extend(citation_candidates(&element));
+        }
+    }
+    lines
+}
+
+/// Candidate lines from elements following a prescribed/required/recommended
+/// reading heading, up to (but not including) the next heading
+fn heading_lines(document: &Html) -> Vec<String> {
+    let Ok(heading_selector) = Selector::parse("h1,h2,h3,h4") else {
+        return Vec::new();
+    };
+
+    let mut lines = Vec::new();
+    for heading in document.select(&heading_selector) {
+        let text: String = heading.text().collect();
+        if !is_reading_heading(&text) {
+            continue;
+        }
+
+        let mut node = heading.next_sibling();
+        while let Some(sibling) = node {
+            if let Some(element) = ElementRef::wrap(sibling) {
+                if matches!(element.value().name(), "h1" | "h2" | "h3" | "h4") {
+                    break;
+                }
+                lines.extend(citation_candidates(&element));
+            }
+            node = sibling.next_sibling();
+        }
+    }
+
+    lines
+}
+
+/// Split a block into one candidate line per `li`/`p`, falling back to the
+/// block's own text if it contains neither
+fn citation_candidates(element: &ElementRef) -> Vec<String> {
+    let Ok(item_selector) = Selector::parse("li,p") else {
+        return Vec::new();
+    };
+
+    let items: Vec<String> = element
+        .select(&item_selector)
+        .map(|item| item.text().collect::<String>().trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if !items.is_empty() {
+        return items;
+    }
+
+    let text: String = element.text().collect::<String>().trim().to_string();
+    if text.is_empty() {
+        Vec::new()
+    } else {
+        vec![text]
+    }
+}
+
+/// Parse one citation-shaped line into a `PrescribedBook`, or `None` if it
+/// doesn't look like a reference
+fn parse_citation_line(line: &str) -> Option<PrescribedBook> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    let page = page_from_line(line);
+
+    parse_chapter_of(line, page.clone()).or_else(|| parse_author_title_edition(line, page))
+}
+
+/// "Chapter N of Title by Author"
+fn parse_chapter_of(line: &str, page: Option<u32>) -> Option<PrescribedBook> {
+    let re = Regex::new(r"(?i)chapter\s+(\d+|[ivxlc]+)\s+of\s+(.+?)\s+by\s+(.+)").ok()?;
+    let caps = re.captures(line)?;
+
+    Some(PrescribedBook {
+        title: caps[2].trim().trim_matches(|c: char| c == ',' || c == '.').to_string(),
+        author: Some(caps[3].trim().trim_end_matches('.').to_string()),
+        chapter: Some(caps[1].to_string()),
+        page,
+        edition: None,
+        libgen_matches: Vec::new(),
+    })
+}
+
+/// "Author(s), Title, Edition, Publisher, Year" - publisher and year are
+/// consumed to isolate the title/edition but aren't kept, since
+/// `PrescribedBook` doesn't track them. The publisher is recognised by its
+/// position: everything after the edition, or else the part before the year.
+fn parse_author_title_edition(line: &str, page: Option<u32>) -> Option<PrescribedBook> {
+    let mut parts: Vec<&str> = line.split(',').map(|p| p.trim()).filter(|p| !p.is_empty()).collect();
+    if parts.len() < 2 {
+        return None;
+    }
+
+    let had_year = Regex::new(r"^(19|20)\d{2}$").ok()?.is_match(parts[parts.len() - 1]);
+    if had_year {
+        parts.pop();
+    }
+
+    let edition_re = Regex::new(r"(?i)^\d+(st|nd|rd|th)?\s*ed(ition)?\.?$").ok()?;
+    let edition_idx = parts.iter().position(|p| edition_re.is_match(p));
+    let edition = edition_idx.map(|i| parts.remove(i).to_string());
+
+    match edition_idx {
+        Some(i) => parts.truncate(i),
+        None if had_year && parts.len() > 2 => {
+            parts.pop();
+        }
+        None => {}
+    }
+
+    if parts.len() < 2 {
+        return None;
+    }
+
+    let author = parts[0].to_string();
+    let title = parts[1..].join(", ");
+    if title.is_empty() {
+        return None;
+    }
+
+    Some(PrescribedBook {
+        title,
+        author: Some(author),
+        chapter: None,
+        page,
+        edition,
+        libgen_matches: Vec::new(),
+    })
+}
+
+/// First `pp. N` / `p. N` page number mentioned in the line
+fn page_from_line(line: &str) -> Option<u32> {
+    let re = Regex::new(r"(?i)pp?\.\s*(\d+)").ok()?;
+    re.captures(line)?.get(1)?.as_str().parse().ok()
+}
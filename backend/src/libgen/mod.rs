@@ -2,7 +2,9 @@
 //! Provides search and download functionality for books
 
 pub mod client;
+pub mod detect;
 pub mod types;
 
 pub use client::LibGenClient;
+pub use detect::detect_prescribed_books;
 pub use types::*;
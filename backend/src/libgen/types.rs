@@ -34,4 +34,7 @@ pub struct PrescribedBook {
     pub chapter: Option<String>,
     pub page: Option<u32>,
     pub edition: Option<String>,
+    /// Candidate LibGen matches for this citation, resolved by title/author search
+    #[serde(default)]
+    pub libgen_matches: Vec<Book>,
 }
@@ -11,8 +11,16 @@ mod error;
 mod moodle;
 mod content;
 mod libgen;
+mod citation;
+mod embeddings;
+mod epub;
+mod render;
 mod cache;
+mod metrics;
+mod session;
+mod auth;
 mod routes;
+mod openapi;
 
 use axum::{
     Router,
@@ -21,6 +29,8 @@ use axum::{
 use tower_http::cors::{CorsLayer, Any};
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 #[tokio::main]
 async fn main() {
@@ -40,6 +50,21 @@ async fn main() {
     tracing::info!("Starting MyLMS Backend on {}:{}", config.host, config.port);
     tracing::info!("Moodle URL: {}", config.moodle_url);
 
+    // Derive the AES-256-GCM key used to encrypt cached tokens/sessions at rest
+    cache::crypto::init(&config);
+
+    // Select the Redis or in-memory cache backend
+    cache::init(&config).await;
+
+    // Set up the content pipeline's disk cache tier (memory -> disk -> network)
+    cache::disk::init(&config);
+
+    // Install the Prometheus recorder backing the /metrics endpoint
+    metrics::init();
+
+    // Start the background PDF export worker
+    routes::export::spawn_worker(config.clone());
+
     // Build CORS layer - very permissive for development
     let cors = CorsLayer::new()
         .allow_methods([Method::GET, Method::POST, Method::DELETE, Method::OPTIONS, Method::PUT, Method::PATCH])
@@ -54,6 +79,8 @@ async fn main() {
     // Build the router
     let app = Router::new()
         .nest("/api", routes::api_routes())
+        .merge(SwaggerUi::new("/api/docs").url("/api/openapi.json", openapi::ApiDoc::openapi()))
+        .route("/metrics", axum::routing::get(metrics::handler))
         .layer(cors)
         .layer(TraceLayer::new_for_http())
         .with_state(config);
@@ -0,0 +1,77 @@
+//! Prometheus metrics for the content pipeline
+//!
+//! Wraps `metrics-exporter-prometheus`, installed once at startup by `init`,
+//! and serves the rendered snapshot via `handler` on `/metrics`. Call sites
+//! go through the thin wrapper functions below rather than the `metrics`
+//! crate's macros directly, so every metric name and label set lives here.
+
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+
+static HANDLE: OnceLock<PrometheusHandle> = OnceLock::new();
+
+/// Install the global Prometheus recorder; must be called once at startup
+pub fn init() {
+    match PrometheusBuilder::new().install_recorder() {
+        Ok(handle) => {
+            HANDLE.set(handle).ok();
+        }
+        Err(e) => tracing::error!("Failed to install Prometheus recorder: {}", e),
+    }
+}
+
+/// Serve the current metrics snapshot in Prometheus text exposition format
+pub async fn handler() -> String {
+    HANDLE.get().map(|h| h.render()).unwrap_or_default()
+}
+
+/// Record a cache lookup outcome for `tier` (`"memory"`/`"disk"`)
+pub fn record_cache_lookup(tier: &'static str, hit: bool) {
+    let outcome = if hit { "hit" } else { "miss" };
+    metrics::counter!("content_cache_lookups_total", "tier" => tier, "outcome" => outcome).increment(1);
+}
+
+/// Record whether an activity was served successfully (from cache or origin)
+pub fn record_activity_fetch(success: bool) {
+    let outcome = if success { "success" } else { "error" };
+    metrics::counter!("content_activity_fetches_total", "outcome" => outcome).increment(1);
+}
+
+/// Record bytes read off the wire for a single Moodle file download
+pub fn record_bytes_downloaded(bytes: u64) {
+    metrics::counter!("content_bytes_downloaded_total").increment(bytes);
+}
+
+/// Record a batch prefetch's requested vs. loaded item counts
+pub fn record_batch(total: usize, loaded: usize) {
+    metrics::counter!("content_batch_items_requested_total").increment(total as u64);
+    metrics::counter!("content_batch_items_loaded_total").increment(loaded as u64);
+}
+
+/// Record a `/content/cache` clear attempt
+pub fn record_cache_clear(success: bool) {
+    let outcome = if success { "success" } else { "error" };
+    metrics::counter!("content_cache_clears_total", "outcome" => outcome).increment(1);
+}
+
+/// Records a histogram duration (seconds) under `name` when dropped, so a
+/// function with several early returns only needs one timer at the top
+/// instead of repeating the measurement at every return point
+pub struct DurationTimer {
+    name: &'static str,
+    start: Instant,
+}
+
+impl DurationTimer {
+    pub fn start(name: &'static str) -> Self {
+        Self { name, start: Instant::now() }
+    }
+}
+
+impl Drop for DurationTimer {
+    fn drop(&mut self) {
+        metrics::histogram!(self.name).record(self.start.elapsed().as_secs_f64());
+    }
+}
@@ -2,24 +2,84 @@
 
 use reqwest::Client;
 use serde_json::Value;
+use std::time::Duration;
 
 use crate::config::Config;
 use crate::error::{AppError, Result};
 use super::types::*;
 
+/// `ETag`/`Last-Modified` validators identifying a specific version of a downloaded file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl FileValidators {
+    fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        Self {
+            etag: headers.get(reqwest::header::ETAG).and_then(|v| v.to_str().ok()).map(|s| s.to_string()),
+            last_modified: headers.get(reqwest::header::LAST_MODIFIED).and_then(|v| v.to_str().ok()).map(|s| s.to_string()),
+        }
+    }
+
+    /// Whether there's anything here worth sending as `If-None-Match`/`If-Modified-Since`
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Outcome of a conditional file fetch
+#[derive(Debug)]
+pub enum ConditionalFile {
+    /// The server confirmed the previously-downloaded body is still current
+    NotModified,
+    /// The server returned a fresh body, with updated validators
+    Modified { body: String, validators: FileValidators },
+}
+
 /// Client for interacting with Moodle's Web Services API
 #[derive(Clone)]
 pub struct MoodleClient {
     http: Client,
     base_url: String,
+    moodle_url: String,
+    max_download_bytes: usize,
+    download_timeout: Duration,
 }
 
 impl MoodleClient {
+    /// The base URL of the Moodle instance this client talks to
+    pub fn moodle_url(&self) -> &str {
+        &self.moodle_url
+    }
+
+    /// Whether `url_str` points at this client's configured Moodle host
+    ///
+    /// Used to guard callers (embedded-image fetches in exports/EPUBs) that
+    /// take a URL out of page content before handing it to `download_bytes`/
+    /// `fetch_file`, which append the caller's live Moodle token as a query
+    /// parameter - a substring/`contains` check on the raw URL isn't enough
+    /// (e.g. `http://evil.com/?x=https://real-host/` contains the real host
+    /// as a substring), so this parses both URLs and compares hosts.
+    pub fn is_own_host(&self, url_str: &str) -> bool {
+        let Ok(configured) = reqwest::Url::parse(&self.moodle_url) else {
+            return false;
+        };
+        let Ok(target) = reqwest::Url::parse(url_str) else {
+            return false;
+        };
+        target.host_str().is_some() && target.host_str() == configured.host_str()
+    }
+
     /// Create a new Moodle client
     pub fn new(config: &Config) -> Self {
         Self {
             http: Client::new(),
             base_url: config.webservice_url(),
+            moodle_url: config.moodle_url.clone(),
+            max_download_bytes: config.max_download_bytes,
+            download_timeout: config.download_timeout,
         }
     }
 
@@ -67,6 +127,38 @@ impl MoodleClient {
         })
     }
 
+    /// Exchange a username/password for a web-service token
+    ///
+    /// GET {moodle_url}/login/token.php?username=...&password=...&service=...
+    pub async fn get_token(&self, username: &str, password: &str, service: &str) -> Result<TokenResponse> {
+        let url = format!("{}/login/token.php", self.moodle_url);
+
+        tracing::debug!("Requesting Moodle token for user: {}", username);
+
+        let response = self.http
+            .get(&url)
+            .query(&[("username", username), ("password", password), ("service", service)])
+            .send()
+            .await?;
+
+        let text = response.text().await?;
+
+        // Check for Moodle error response (e.g. invalidlogin)
+        if let Ok(error) = serde_json::from_str::<MoodleError>(&text) {
+            if error.is_error() {
+                let msg = error.message.unwrap_or_else(||
+                    error.errorcode.unwrap_or_else(|| "Invalid credentials".to_string())
+                );
+                return Err(AppError::InvalidCredentials(msg));
+            }
+        }
+
+        serde_json::from_str(&text).map_err(|e| {
+            tracing::error!("Failed to parse Moodle token response: {}", e);
+            AppError::Internal(format!("Failed to parse response: {}", e))
+        })
+    }
+
     /// Get site information (also validates the token)
     pub async fn get_site_info(&self, token: &str) -> Result<SiteInfo> {
         self.call(token, "core_webservice_get_site_info", &[]).await
@@ -95,6 +187,141 @@ impl MoodleClient {
 
     /// Download a file from Moodle with token authentication
     pub async fn download_file(&self, token: &str, file_url: &str) -> Result<String> {
+        let (content, _validators) = self.download_file_with_validators(token, file_url).await?;
+        Ok(content)
+    }
+
+    /// Download a file from Moodle, also surfacing its `ETag`/`Last-Modified`
+    /// validators so the caller can revalidate instead of re-downloading later
+    ///
+    /// The whole fetch is bounded by `download_timeout`, and the body is read
+    /// chunk-by-chunk so an oversized response aborts with `ContentTooLarge`
+    /// instead of buffering unboundedly.
+    pub async fn download_file_with_validators(&self, token: &str, file_url: &str) -> Result<(String, FileValidators)> {
+        let url = Self::with_token(file_url, token);
+        let _timer = crate::metrics::DurationTimer::start("moodle_download_file_duration_seconds");
+
+        tracing::debug!("Downloading file from Moodle...");
+
+        tokio::time::timeout(self.download_timeout, async {
+            let mut response = self.http.get(&url).send().await?;
+
+            if !response.status().is_success() {
+                return Err(AppError::NotFound(format!(
+                    "Failed to download file: HTTP {}",
+                    response.status()
+                )));
+            }
+
+            let validators = FileValidators::from_headers(response.headers());
+            let content = self.read_body_limited(&mut response).await?;
+
+            // Check if it's an error response
+            if content.trim().starts_with('{') && content.contains("\"error\"") {
+                return Err(AppError::MoodleApi("File download returned error".to_string()));
+            }
+
+            Ok((content, validators))
+        })
+        .await
+        .map_err(|_| AppError::Internal(format!("Download timed out after {:?}", self.download_timeout)))?
+    }
+
+    /// Revalidate a previously-downloaded file against its stored `ETag`/`Last-Modified`
+    ///
+    /// Issues a conditional GET (`If-None-Match`/`If-Modified-Since`). A `304`
+    /// confirms the cached body is still current; any other success status is
+    /// treated as fresh content with updated validators. Bounded by
+    /// `download_timeout`/`max_download_bytes` like `download_file_with_validators`.
+    pub async fn download_file_conditional(
+        &self,
+        token: &str,
+        file_url: &str,
+        validators: &FileValidators,
+    ) -> Result<ConditionalFile> {
+        let url = Self::with_token(file_url, token);
+
+        tracing::debug!("Revalidating file from Moodle...");
+
+        tokio::time::timeout(self.download_timeout, async {
+            let mut request = self.http.get(&url);
+            if let Some(etag) = &validators.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+
+            let mut response = request.send().await?;
+
+            if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+                return Ok(ConditionalFile::NotModified);
+            }
+
+            if !response.status().is_success() {
+                return Err(AppError::NotFound(format!(
+                    "Failed to revalidate file: HTTP {}",
+                    response.status()
+                )));
+            }
+
+            let validators = FileValidators::from_headers(response.headers());
+            let content = self.read_body_limited(&mut response).await?;
+
+            Ok(ConditionalFile::Modified { body: content, validators })
+        })
+        .await
+        .map_err(|_| AppError::Internal(format!("Revalidation timed out after {:?}", self.download_timeout)))?
+    }
+
+    /// Append a Moodle webservice token to `file_url`, unless it already carries one
+    fn with_token(file_url: &str, token: &str) -> String {
+        if file_url.contains("token=") || file_url.contains("wstoken=") {
+            file_url.to_string()
+        } else {
+            let separator = if file_url.contains('?') { "&" } else { "?" };
+            format!("{}{}token={}", file_url, separator, token)
+        }
+    }
+
+    /// Read a response body chunk-by-chunk, aborting with `ContentTooLarge`
+    /// once `max_download_bytes` is exceeded instead of buffering unboundedly
+    async fn read_body_limited(&self, response: &mut reqwest::Response) -> Result<String> {
+        let mut buf: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            buf.extend_from_slice(&chunk);
+            if buf.len() > self.max_download_bytes {
+                return Err(AppError::ContentTooLarge(self.max_download_bytes));
+            }
+        }
+
+        crate::metrics::record_bytes_downloaded(buf.len() as u64);
+
+        String::from_utf8(buf).map_err(|e| AppError::Internal(format!("Downloaded file was not valid UTF-8: {}", e)))
+    }
+
+    /// Read a response body chunk-by-chunk like `read_body_limited`, but without
+    /// requiring valid UTF-8, for binary downloads
+    async fn read_bytes_limited(&self, response: &mut reqwest::Response) -> Result<Vec<u8>> {
+        let mut buf: Vec<u8> = Vec::new();
+
+        while let Some(chunk) = response.chunk().await? {
+            buf.extend_from_slice(&chunk);
+            if buf.len() > self.max_download_bytes {
+                return Err(AppError::ContentTooLarge(self.max_download_bytes));
+            }
+        }
+
+        crate::metrics::record_bytes_downloaded(buf.len() as u64);
+
+        Ok(buf)
+    }
+
+    /// Download a file's raw bytes (and detected content-type) from Moodle with token authentication
+    ///
+    /// Bounded by `download_timeout`/`max_download_bytes` like `download_file_with_validators`.
+    pub async fn download_bytes(&self, token: &str, file_url: &str) -> Result<(Vec<u8>, Option<String>)> {
         let url = if file_url.contains("token=") || file_url.contains("wstoken=") {
             file_url.to_string()
         } else {
@@ -102,26 +329,55 @@ impl MoodleClient {
             format!("{}{}token={}", file_url, separator, token)
         };
 
-        tracing::debug!("Downloading file from Moodle...");
+        tracing::debug!("Downloading binary file from Moodle...");
 
-        let response = self.http.get(&url).send().await?;
-        
-        if !response.status().is_success() {
-            return Err(AppError::NotFound(format!(
-                "Failed to download file: HTTP {}",
-                response.status()
-            )));
-        }
+        tokio::time::timeout(self.download_timeout, async {
+            let mut response = self.http.get(&url).send().await?;
 
-        let content = response.text().await?;
+            if !response.status().is_success() {
+                return Err(AppError::NotFound(format!(
+                    "Failed to download file: HTTP {}",
+                    response.status()
+                )));
+            }
+
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string());
 
+            let bytes = self.read_bytes_limited(&mut response).await?;
 
-        // Check if it's an error response
-        if content.trim().starts_with('{') && content.contains("\"error\"") {
-            return Err(AppError::MoodleApi("File download returned error".to_string()));
+            Ok((bytes, content_type))
+        })
+        .await
+        .map_err(|_| AppError::Internal(format!("Download timed out after {:?}", self.download_timeout)))?
+    }
+
+    /// Fetch a protected file directly, optionally forwarding a `Range` header
+    ///
+    /// Unlike `download_file`, this returns the raw `reqwest::Response` so the
+    /// caller can stream the body through without buffering it in memory - only
+    /// the initial request (headers/connect) is bounded by `download_timeout`,
+    /// since the body is left to the caller to stream onward.
+    pub async fn fetch_file(&self, token: &str, file_url: &str, range: Option<&str>) -> Result<reqwest::Response> {
+        let url = if file_url.contains("token=") || file_url.contains("wstoken=") {
+            file_url.to_string()
+        } else {
+            let separator = if file_url.contains('?') { "&" } else { "?" };
+            format!("{}{}token={}", file_url, separator, token)
+        };
+
+        let mut request = self.http.get(&url);
+        if let Some(range) = range {
+            request = request.header(reqwest::header::RANGE, range);
         }
 
-        Ok(content)
+        tokio::time::timeout(self.download_timeout, request.send())
+            .await
+            .map_err(|_| AppError::Internal(format!("Fetch timed out after {:?}", self.download_timeout)))?
+            .map_err(AppError::from)
     }
 
     /// Get page module content
@@ -0,0 +1,8 @@
+//! Moodle integration module
+//! Provides a client and data types for Moodle's Web Services API
+
+pub mod client;
+pub mod types;
+
+pub use client::{ConditionalFile, FileValidators, MoodleClient};
+pub use types::*;
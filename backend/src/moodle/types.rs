@@ -1,9 +1,10 @@
 //! Moodle data types - ported from TypeScript
 
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Moodle site information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SiteInfo {
     pub username: String,
     pub firstname: String,
@@ -20,7 +21,7 @@ pub struct SiteInfo {
 }
 
 /// Moodle course
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Course {
     pub id: i64,
     pub shortname: String,
@@ -47,7 +48,7 @@ pub struct Course {
 }
 
 /// Course section containing modules
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CourseSection {
     pub id: i64,
     pub name: String,
@@ -64,7 +65,7 @@ pub struct CourseSection {
 }
 
 /// Course module (activity)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CourseModule {
     pub id: i64,
     #[serde(default)]
@@ -93,7 +94,7 @@ pub struct CourseModule {
 }
 
 /// Module content (file, etc.)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ModuleContent {
     #[serde(rename = "type")]
     pub content_type: String,
@@ -113,7 +114,7 @@ pub struct ModuleContent {
 }
 
 /// Activity representation for frontend
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Activity {
     pub id: String,
     pub name: String,
@@ -125,6 +126,14 @@ pub struct Activity {
     pub completed: Option<bool>,
 }
 
+/// Response from Moodle's `login/token.php` credential exchange
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenResponse {
+    pub token: String,
+    #[serde(default)]
+    pub privatetoken: Option<String>,
+}
+
 /// Moodle API error response
 #[derive(Debug, Clone, Deserialize)]
 pub struct MoodleError {
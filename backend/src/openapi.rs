@@ -0,0 +1,54 @@
+//! OpenAPI spec generation and Swagger UI
+//!
+//! Annotates the REST surface with `utoipa` so the Next.js frontend can
+//! codegen a typed client instead of hand-maintaining request/response shapes.
+
+use utoipa::OpenApi;
+
+use crate::{moodle, routes};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        routes::auth::login,
+        routes::auth::login_with_credentials,
+        routes::auth::logout,
+        routes::auth::validate_token,
+        routes::courses::get_courses,
+        routes::courses::get_course_contents,
+        routes::export::export_pdf,
+        routes::export::get_export_status,
+        routes::export::download_export,
+        routes::media::get_media,
+    ),
+    components(schemas(
+        routes::auth::LoginRequest,
+        routes::auth::LoginResponse,
+        routes::auth::UserInfo,
+        routes::auth::TokenLoginRequest,
+        routes::auth::TokenLoginResponse,
+        routes::courses::CoursesResponse,
+        routes::courses::CourseContentsResponse,
+        routes::courses::SectionWithActivities,
+        routes::export::ExportPdfRequest,
+        routes::export::ExportSection,
+        routes::export::ExportErrorResponse,
+        routes::export::EnqueuedResponse,
+        routes::export::JobStatusResponse,
+        crate::citation::BibEntry,
+        moodle::SiteInfo,
+        moodle::Course,
+        moodle::CourseSection,
+        moodle::CourseModule,
+        moodle::ModuleContent,
+        moodle::Activity,
+        crate::error::ErrorResponse,
+    )),
+    tags(
+        (name = "auth", description = "Authentication endpoints"),
+        (name = "courses", description = "Course browsing endpoints"),
+        (name = "export", description = "Export endpoints"),
+        (name = "media", description = "Protected course file proxy"),
+    )
+)]
+pub struct ApiDoc;
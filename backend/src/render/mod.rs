@@ -0,0 +1,11 @@
+//! HTML-to-Markdown rendering
+//!
+//! Converts already-cleaned course HTML into Markdown, preserving headings,
+//! lists, tables, links, and image references, so it can be stored as plain
+//! text or fed into note-taking tools - the conversion step webnovel/article
+//! archivers run before packaging.
+
+/// Convert cleaned HTML to Markdown
+pub fn to_markdown(html: &str) -> String {
+    html2md::parse_html(html)
+}
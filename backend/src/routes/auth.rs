@@ -2,22 +2,43 @@
 
 use axum::{
     extract::State,
-    http::StatusCode,
+    http::{header, HeaderMap},
     routing::post,
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::config::Config;
-use crate::error::{AppError, Result};
+use crate::error::Result;
 use crate::moodle::MoodleClient;
+use crate::session::{self, Session};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
     token: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TokenLoginRequest {
+    username: String,
+    password: String,
+    #[serde(default = "default_service")]
+    service: String,
+}
+
+fn default_service() -> String {
+    "moodle_mobile_app".to_string()
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TokenLoginResponse {
+    success: bool,
+    token: String,
+    user: UserInfo,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LoginResponse {
     success: bool,
     user: Option<UserInfo>,
@@ -25,7 +46,7 @@ pub struct LoginResponse {
     error: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserInfo {
     userid: i64,
     username: String,
@@ -38,26 +59,41 @@ pub struct UserInfo {
 pub fn routes() -> Router<Config> {
     Router::new()
         .route("/login", post(login))
+        .route("/token", post(login_with_credentials))
         .route("/validate", post(validate_token))
+        .route("/logout", post(logout))
 }
 
 /// Login with Moodle token
-/// 
-/// POST /api/auth/login
-/// Body: { "token": "..." }
-async fn login(
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Login result", body = LoginResponse),
+        (status = 502, description = "Moodle API error", body = crate::error::ErrorResponse),
+    )
+)]
+pub(crate) async fn login(
     State(config): State<Config>,
     Json(body): Json<LoginRequest>,
-) -> Result<Json<LoginResponse>> {
+) -> Result<(HeaderMap, Json<LoginResponse>)> {
     tracing::info!("Login attempt with token");
-    
+
     let client = MoodleClient::new(&config);
-    
+
     match client.get_site_info(&body.token).await {
         Ok(site_info) => {
             tracing::info!("Login successful for user: {}", site_info.fullname);
-            
-            Ok(Json(LoginResponse {
+
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::SET_COOKIE,
+                session::create_session(&config, body.token.clone(), site_info.userid, site_info.fullname.clone()).await?,
+            );
+
+            Ok((headers, Json(LoginResponse {
                 success: true,
                 user: Some(UserInfo {
                     userid: site_info.userid,
@@ -66,24 +102,91 @@ async fn login(
                     userpictureurl: site_info.userpictureurl,
                 }),
                 error: None,
-            }))
+            })))
         }
         Err(e) => {
             tracing::warn!("Login failed: {}", e);
-            Ok(Json(LoginResponse {
+            Ok((HeaderMap::new(), Json(LoginResponse {
                 success: false,
                 user: None,
                 error: Some(e.to_string()),
-            }))
+            })))
         }
     }
 }
 
+/// Exchange a Moodle username/password for a web-service token
+#[utoipa::path(
+    post,
+    path = "/api/auth/token",
+    tag = "auth",
+    request_body = TokenLoginRequest,
+    responses(
+        (status = 200, description = "Token and user info", body = TokenLoginResponse),
+        (status = 401, description = "Invalid credentials", body = crate::error::ErrorResponse),
+    )
+)]
+pub(crate) async fn login_with_credentials(
+    State(config): State<Config>,
+    Json(body): Json<TokenLoginRequest>,
+) -> Result<(HeaderMap, Json<TokenLoginResponse>)> {
+    tracing::info!("Token login attempt for user: {}", body.username);
+
+    let client = MoodleClient::new(&config);
+
+    let token_response = client.get_token(&body.username, &body.password, &body.service).await?;
+    let site_info = client.get_site_info(&token_response.token).await?;
+
+    tracing::info!("Token login successful for user: {}", site_info.fullname);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::SET_COOKIE,
+        session::create_session(&config, token_response.token.clone(), site_info.userid, site_info.fullname.clone()).await?,
+    );
+
+    Ok((headers, Json(TokenLoginResponse {
+        success: true,
+        token: token_response.token,
+        user: UserInfo {
+            userid: site_info.userid,
+            username: site_info.username,
+            fullname: site_info.fullname,
+            userpictureurl: site_info.userpictureurl,
+        },
+    })))
+}
+
+/// Log out and clear the session
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "auth",
+    responses(
+        (status = 200, description = "Session cleared"),
+        (status = 401, description = "Not authenticated", body = crate::error::ErrorResponse),
+    )
+)]
+pub(crate) async fn logout(session: Session) -> (HeaderMap, Json<serde_json::Value>) {
+    session::evict_session(&session.id).await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::SET_COOKIE, session::clear_cookie());
+
+    (headers, Json(serde_json::json!({ "success": true })))
+}
+
 /// Validate a token without full login
-/// 
-/// POST /api/auth/validate
-/// Body: { "token": "..." }
-async fn validate_token(
+#[utoipa::path(
+    post,
+    path = "/api/auth/validate",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Whether the token is valid"),
+    )
+)]
+pub(crate) async fn validate_token(
     State(config): State<Config>,
     Json(body): Json<LoginRequest>,
 ) -> Result<Json<serde_json::Value>> {
@@ -1,16 +1,20 @@
 //! Book-related API routes for searching and downloading books via LibGen
 
 use axum::{
-    extract::Query,
-    http::StatusCode,
-    response::Json,
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
     routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
 
+use crate::auth::Authed;
 use crate::config::Config;
-use crate::libgen::{LibGenClient, SearchResult, PrescribedBook};
+use crate::epub::{self, BookMeta, CleanedChapter};
+use crate::libgen::{self, LibGenClient, SearchResult, PrescribedBook};
+use crate::moodle::MoodleClient;
+use crate::render;
 
 /// Query params for book search
 #[derive(Debug, Deserialize)]
@@ -47,12 +51,34 @@ pub struct DetectResponse {
     books: Vec<PrescribedBook>,
 }
 
+/// Request to assemble cleaned pages into a downloadable EPUB
+#[derive(Debug, Deserialize)]
+pub struct ExportEpubRequest {
+    title: String,
+    #[serde(default)]
+    author: Option<String>,
+    #[serde(default)]
+    language: Option<String>,
+    /// Already-cleaned pages (see `content::clean_html_with_token`), one per chapter
+    chapters: Vec<CleanedChapter>,
+}
+
+/// Request to render cleaned pages as a single Markdown document
+#[derive(Debug, Deserialize)]
+pub struct ExportMarkdownRequest {
+    title: String,
+    /// Already-cleaned pages (see `content::clean_html_with_token`), one per chapter
+    chapters: Vec<CleanedChapter>,
+}
+
 /// Create books routes
 pub fn books_routes() -> Router<Config> {
     Router::new()
         .route("/search", get(search_books))
         .route("/download/{md5}", get(get_download_url))
         .route("/detect", post(detect_books))
+        .route("/export/epub", post(export_epub))
+        .route("/export/markdown", post(export_markdown))
 }
 
 /// Search for books by title/author
@@ -105,39 +131,108 @@ async fn get_download_url(
     }
 }
 
-/// Detect prescribed books from HTML content
+/// Maximum LibGen candidates attached to each detected citation
+const MAX_LIBGEN_MATCHES: usize = 3;
+
+/// Detect prescribed books from HTML content and resolve each against LibGen
 async fn detect_books(
     Json(payload): Json<DetectRequest>,
 ) -> Result<Json<DetectResponse>, (StatusCode, String)> {
     tracing::info!("Book detection request, HTML length: {}", payload.html.len());
-    
-    // TODO: Implement book detection from HTML
-    // For now, return empty array
-    let books = detect_prescribed_books(&payload.html);
-    
+
+    let mut books = libgen::detect_prescribed_books(&payload.html);
+
+    let client = LibGenClient::new();
+    for book in &mut books {
+        let query = match &book.author {
+            Some(author) => format!("{} {}", book.title, author),
+            None => book.title.clone(),
+        };
+
+        match client.search(&query).await {
+            Ok(results) => {
+                book.libgen_matches = results.books.into_iter().take(MAX_LIBGEN_MATCHES).collect();
+            }
+            Err(e) => {
+                tracing::warn!("LibGen lookup failed for prescribed book '{}': {}", book.title, e);
+            }
+        }
+    }
+
     Ok(Json(DetectResponse {
         success: true,
         books,
     }))
 }
 
-/// Extract prescribed book references from HTML content
-fn detect_prescribed_books(_html: &str) -> Vec<PrescribedBook> {
-    let books = Vec::new();
-    
-    // Look for common patterns in prescribed reading sections
-    // Pattern 1: "Author, Title, Edition, Year"
-    // Pattern 2: "Chapter X of Title by Author"
-    // Pattern 3: Book titles in specific divs
-    
-    // Simple regex-based detection for now
-    // Look for patterns like "Chapter 1" or "pp. 10-20"
-    let _chapter_pattern = regex::Regex::new(r"(?i)chapter\s+(\d+|[ivxlc]+)").ok();
-    let _page_pattern = regex::Regex::new(r"(?i)pp?\.\s*(\d+)").ok();
-    
-    // TODO: Implement more sophisticated book detection
-    // This will need to parse the HTML and find prescribed reading sections
-    // Then extract book titles, authors, chapters, and pages
-    
-    books
+/// Assemble cleaned pages into a downloadable EPUB
+///
+/// Images in the cleaned HTML are downloaded with the caller's Moodle token
+/// and embedded as resources, since a protected `pluginfile.php` URL left
+/// in place would be unreachable from an offline EPUB reader.
+///
+/// Requires a session cookie or bearer token (takes `Authed`)
+async fn export_epub(
+    State(config): State<Config>,
+    Authed(auth): Authed,
+    Json(request): Json<ExportEpubRequest>,
+) -> crate::error::Result<impl IntoResponse> {
+    tracing::info!("EPUB export request: {} chapters, title: {}", request.chapters.len(), request.title);
+
+    let filename = sanitize_filename(&request.title);
+    let meta = BookMeta {
+        title: request.title,
+        author: request.author,
+        language: request.language,
+    };
+
+    let client = MoodleClient::new(&config);
+    let bytes = epub::build_epub(request.chapters, meta, &client, &auth.token).await?;
+
+    let content_disposition = format!("attachment; filename=\"{}.epub\"", filename);
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/epub+zip".to_string()),
+            (header::CONTENT_DISPOSITION, content_disposition),
+        ],
+        bytes,
+    ))
+}
+
+/// Strip characters that don't belong in a `Content-Disposition` filename
+fn sanitize_filename(title: &str) -> String {
+    let cleaned: String = title
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' { c } else { ' ' })
+        .collect();
+    let cleaned = cleaned.split_whitespace().collect::<Vec<_>>().join("_");
+    if cleaned.is_empty() { "book".to_string() } else { cleaned }
+}
+
+/// Render cleaned pages as a single Markdown document: the title as a `#`
+/// heading, then one `##` section per chapter
+///
+/// No authentication required - the input is already-cleaned HTML, no Moodle token needed
+async fn export_markdown(Json(request): Json<ExportMarkdownRequest>) -> impl IntoResponse {
+    tracing::info!("Markdown export request: {} chapters, title: {}", request.chapters.len(), request.title);
+
+    let mut doc = format!("# {}\n\n", request.title);
+    for chapter in &request.chapters {
+        doc.push_str(&format!("## {}\n\n", chapter.title));
+        doc.push_str(&render::to_markdown(&chapter.html));
+        doc.push_str("\n\n");
+    }
+
+    let content_disposition = format!("attachment; filename=\"{}.md\"", sanitize_filename(&request.title));
+
+    (
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "text/markdown; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, content_disposition),
+        ],
+        doc,
+    )
 }
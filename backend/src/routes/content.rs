@@ -3,6 +3,7 @@
 use axum::{
     extract::{Query, State},
     http::header::AUTHORIZATION,
+    response::sse::{Event, Sse},
     routing::{get, delete, post},
     Json, Router,
 };
@@ -10,16 +11,168 @@ use axum::http::HeaderMap;
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 use futures::future::join_all;
+use futures::stream::{FuturesUnordered, Stream, StreamExt};
 
-use crate::cache::{MemoryCache, CACHE};
+use crate::auth::Authed;
+use crate::cache::{self, MemoryCache};
 use crate::config::Config;
-use crate::content::clean_html_with_token;
+use crate::content::{clean_html_with_token, extract_reader_content};
+use crate::embeddings::{embed_cached, HashingEmbedder, INDEX};
 use crate::error::{AppError, Result};
-use crate::moodle::MoodleClient;
+use crate::moodle::{ConditionalFile, FileValidators, MoodleClient};
+
+/// How long a cached body is served as-is before a revalidation round-trip is made
+const FRESH_WINDOW_SECS: u64 = 300;
+/// Outer TTL after which a cache entry is evicted outright
+const CACHE_TTL: Duration = Duration::from_secs(3600);
+
+/// Cached activity body plus the validators needed to revalidate it instead
+/// of blindly re-downloading and re-cleaning once `FRESH_WINDOW_SECS` has passed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedActivity {
+    body: String,
+    /// The single source file this body came from, when there was exactly one -
+    /// bodies combined from several files have no single validator source and
+    /// so aren't revalidatable
+    source_url: Option<String>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fetched_at: u64,
+}
+
+impl CachedActivity {
+    fn now() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    fn is_fresh(&self) -> bool {
+        Self::now().saturating_sub(self.fetched_at) < FRESH_WINDOW_SECS
+    }
+
+    fn validators(&self) -> FileValidators {
+        FileValidators {
+            etag: self.etag.clone(),
+            last_modified: self.last_modified.clone(),
+        }
+    }
+}
+
+/// Result of consulting the activity cache for `cache_key`
+enum CacheLookup {
+    /// Serve this cleaned body as-is - still fresh, or just confirmed fresh by the origin
+    Hit(String),
+    /// Revalidation found a fresh body that still needs cleaning before it can
+    /// be served and re-cached
+    Stale { raw_html: String, source_url: String, validators: FileValidators },
+    /// No usable cache entry - a real miss, a multi-file entry past its fresh window,
+    /// or a malformed cache value
+    Miss,
+}
+
+/// Consult the activity cache (memory, then disk), revalidating with the origin
+/// server if the entry is past its fresh window and was downloaded from a
+/// single source file. A disk hit is promoted back into the memory tier.
+async fn lookup_cache(client: &MoodleClient, token: &str, cache_key: &str) -> CacheLookup {
+    let raw = match cache::get(cache_key).await {
+        Some(raw) => {
+            crate::metrics::record_cache_lookup("memory", true);
+            Some(raw)
+        }
+        None => {
+            crate::metrics::record_cache_lookup("memory", false);
+            let from_disk = cache::disk::get(cache_key).await;
+            crate::metrics::record_cache_lookup("disk", from_disk.is_some());
+            if let Some(raw) = &from_disk {
+                tracing::debug!("Disk cache hit for {}, promoting to memory", cache_key);
+                cache::set(cache_key, raw, Some(CACHE_TTL)).await;
+            }
+            from_disk
+        }
+    };
+
+    let Some(raw) = raw else {
+        return CacheLookup::Miss;
+    };
+    let Ok(mut entry) = serde_json::from_str::<CachedActivity>(&raw) else {
+        return CacheLookup::Miss;
+    };
+
+    if entry.is_fresh() {
+        return CacheLookup::Hit(entry.body);
+    }
+
+    let Some(source_url) = entry.source_url.clone() else {
+        return CacheLookup::Miss;
+    };
+
+    match client.download_file_conditional(token, &source_url, &entry.validators()).await {
+        Ok(ConditionalFile::NotModified) => {
+            tracing::debug!("Revalidated {} - not modified", source_url);
+            entry.fetched_at = CachedActivity::now();
+            if let Ok(json) = serde_json::to_string(&entry) {
+                cache::set(cache_key, &json, Some(CACHE_TTL)).await;
+            }
+            CacheLookup::Hit(entry.body)
+        }
+        Ok(ConditionalFile::Modified { body, validators }) => {
+            tracing::debug!("Revalidated {} - content changed", source_url);
+            CacheLookup::Stale { raw_html: body, source_url, validators }
+        }
+        Err(e) => {
+            tracing::warn!("Revalidation failed for {}, serving stale cache: {}", source_url, e);
+            CacheLookup::Hit(entry.body)
+        }
+    }
+}
+
+/// Cache a cleaned activity body in both tiers, recording its source file's
+/// validators when it came from exactly one file so it can be revalidated next time
+async fn store_cache(cache_key: &str, body: String, source_url: Option<String>, validators: FileValidators) {
+    let entry = CachedActivity {
+        body,
+        source_url,
+        etag: validators.etag,
+        last_modified: validators.last_modified,
+        fetched_at: CachedActivity::now(),
+    };
+
+    if let Ok(json) = serde_json::to_string(&entry) {
+        cache::set(cache_key, &json, Some(CACHE_TTL)).await;
+        cache::disk::set(cache_key, &json, Some(CACHE_TTL)).await;
+    }
+}
 
 #[derive(Debug, Deserialize)]
 pub struct ContentQuery {
     url: String,
+    /// Narrow the cleaned output to the page's main content (readability-style scoring)
+    #[serde(default)]
+    extract_main: bool,
+    /// Rendering mode - `cleaned` (default) or `reader` for a stripped-down,
+    /// cosmetic-filtered structure of headings/paragraphs/links/images
+    #[serde(default)]
+    mode: ContentMode,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentMode {
+    #[default]
+    Cleaned,
+    Reader,
+}
+
+impl ContentMode {
+    /// Cache-key suffix so `cleaned`/`reader` renders of the same URL don't collide
+    fn cache_suffix(&self) -> &'static str {
+        match self {
+            ContentMode::Cleaned => "cleaned",
+            ContentMode::Reader => "reader",
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -60,12 +213,37 @@ pub struct CacheStatusResponse {
     message: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    course_id: i64,
+    q: String,
+    #[serde(default)]
+    top_k: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResultItem {
+    section_id: i64,
+    section_name: String,
+    module_id: Option<i64>,
+    module_name: Option<String>,
+    text: String,
+    score: f32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    results: Vec<SearchResultItem>,
+}
+
 /// Build content routes
 pub fn routes() -> Router<Config> {
     Router::new()
         .route("/activity", get(get_activity_content))
         .route("/batch", post(batch_prefetch))
+        .route("/batch/stream", post(batch_prefetch_stream))
         .route("/cache", delete(clear_cache))
+        .route("/search", get(search_content))
 }
 
 /// Extract token from Authorization header
@@ -77,6 +255,15 @@ fn extract_token(headers: &HeaderMap) -> Result<String> {
         .ok_or(AppError::Unauthorized)
 }
 
+/// Clean `html`, then apply reader-mode extraction on top if requested
+fn render_content(html: &str, token: &str, query: &ContentQuery) -> String {
+    let cleaned = clean_html_with_token(html, Some(token), query.extract_main);
+    match query.mode {
+        ContentMode::Cleaned => cleaned,
+        ContentMode::Reader => extract_reader_content(&cleaned),
+    }
+}
+
 /// Extract module ID (cmid) from Moodle URL
 fn extract_module_id(url: &str) -> Option<i64> {
     // Pattern: ?id=12345 or &id=12345
@@ -98,28 +285,46 @@ async fn get_activity_content(
     headers: HeaderMap,
     Query(query): Query<ContentQuery>,
 ) -> Result<Json<ContentResponse>> {
+    let _timer = crate::metrics::DurationTimer::start("content_get_activity_duration_seconds");
     let token = extract_token(&headers)?;
-    
-    // Check cache first
-    let cache_key = format!("activity:{}", MemoryCache::url_hash(&query.url));
-    
-    if let Some(cached_content) = CACHE.get(&cache_key) {
-        tracing::debug!("Cache hit for {}", &query.url);
-        return Ok(Json(ContentResponse {
-            success: true,
-            content: Some(cached_content),
-            cached: Some(true),
-            error: None,
-        }));
+
+    // Check cache first, revalidating with the origin if it's gone stale
+    let cache_key = format!("activity:{}:{}", query.mode.cache_suffix(), MemoryCache::url_hash(&query.url));
+    let client = MoodleClient::new(&config);
+
+    match lookup_cache(&client, &token, &cache_key).await {
+        CacheLookup::Hit(body) => {
+            tracing::debug!("Cache hit for {}", &query.url);
+            crate::metrics::record_activity_fetch(true);
+            return Ok(Json(ContentResponse {
+                success: true,
+                content: Some(body),
+                cached: Some(true),
+                error: None,
+            }));
+        }
+        CacheLookup::Stale { raw_html, source_url, validators } => {
+            let cleaned = render_content(&raw_html, &token, &query);
+            store_cache(&cache_key, cleaned.clone(), Some(source_url), validators).await;
+            crate::metrics::record_activity_fetch(true);
+            return Ok(Json(ContentResponse {
+                success: true,
+                content: Some(cleaned),
+                cached: Some(false),
+                error: None,
+            }));
+        }
+        CacheLookup::Miss => {}
     }
-    
+
     tracing::info!("Fetching content for: {}", &query.url);
-    
+
     // Extract module ID from URL
     let cmid = match extract_module_id(&query.url) {
         Some(id) => id,
         None => {
             tracing::error!("Could not extract module ID from URL: {}", &query.url);
+            crate::metrics::record_activity_fetch(false);
             return Ok(Json(ContentResponse {
                 success: false,
                 content: None,
@@ -128,16 +333,15 @@ async fn get_activity_content(
             }));
         }
     };
-    
+
     tracing::debug!("Extracted module ID: {}", cmid);
-    
-    let client = MoodleClient::new(&config);
-    
+
     // Step 1: Get module info to find the course ID
     let mod_info = match client.get_course_module(&token, cmid).await {
         Ok(info) => info,
         Err(e) => {
             tracing::error!("Failed to get module info: {}", e);
+            crate::metrics::record_activity_fetch(false);
             return Ok(Json(ContentResponse {
                 success: false,
                 content: None,
@@ -188,13 +392,14 @@ async fn get_activity_content(
     
     if html_files.is_empty() {
         tracing::info!("No HTML files found for module {}, trying direct fetch", cmid);
-        
+
         // Fallback: Try to download the URL directly (works for some resource types)
-        match client.download_file(&token, &query.url).await {
-            Ok(html) => {
-                let cleaned = clean_html_with_token(&html, Some(&token));
-                CACHE.set(&cache_key, &cleaned, Some(Duration::from_secs(3600)));
-                
+        match client.download_file_with_validators(&token, &query.url).await {
+            Ok((html, validators)) => {
+                let cleaned = render_content(&html, &token, &query);
+                store_cache(&cache_key, cleaned.clone(), Some(query.url.clone()), validators).await;
+
+                crate::metrics::record_activity_fetch(true);
                 return Ok(Json(ContentResponse {
                     success: true,
                     content: Some(cleaned),
@@ -203,6 +408,7 @@ async fn get_activity_content(
                 }));
             }
             Err(_) => {
+                crate::metrics::record_activity_fetch(false);
                 return Ok(Json(ContentResponse {
                     success: false,
                     content: None,
@@ -212,26 +418,33 @@ async fn get_activity_content(
             }
         }
     }
-    
+
     tracing::info!("Found {} HTML file(s)", html_files.len());
-    
-    // Step 3: Download and combine HTML files
+
+    // Step 3: Download and combine HTML files. A single file's validators are
+    // kept for revalidation; several files combined have no single source to
+    // revalidate against, so they're just re-downloaded and re-cleaned in full
     let mut combined_html = String::new();
-    
+    let mut single_file_validators: Option<FileValidators> = None;
+
     for (fileurl, filename) in &html_files {
-        match client.download_file(&token, fileurl).await {
-            Ok(html) => {
+        match client.download_file_with_validators(&token, fileurl).await {
+            Ok((html, validators)) => {
                 tracing::debug!("Downloaded: {}", filename);
                 combined_html.push_str(&html);
                 combined_html.push_str("\n\n");
+                if html_files.len() == 1 {
+                    single_file_validators = Some(validators);
+                }
             }
             Err(e) => {
                 tracing::warn!("Failed to download {}: {}", filename, e);
             }
         }
     }
-    
+
     if combined_html.is_empty() {
+        crate::metrics::record_activity_fetch(false);
         return Ok(Json(ContentResponse {
             success: false,
             content: None,
@@ -239,13 +452,14 @@ async fn get_activity_content(
             error: Some("Failed to download any content".to_string()),
         }));
     }
-    
+
     // Step 4: Clean the HTML
-    let cleaned = clean_html_with_token(&combined_html, Some(&token));
-    
-    // Cache for 1 hour
-    CACHE.set(&cache_key, &cleaned, Some(Duration::from_secs(3600)));
-    
+    let cleaned = render_content(&combined_html, &token, &query);
+
+    let source_url = if html_files.len() == 1 { Some(html_files[0].0.clone()) } else { None };
+    store_cache(&cache_key, cleaned.clone(), source_url, single_file_validators.unwrap_or_default()).await;
+
+    crate::metrics::record_activity_fetch(true);
     Ok(Json(ContentResponse {
         success: true,
         content: Some(cleaned),
@@ -256,8 +470,14 @@ async fn get_activity_content(
 
 /// Clear all cached content
 async fn clear_cache() -> Result<Json<CacheStatusResponse>> {
-    if CACHE.clear() {
-        tracing::info!("Cache cleared");
+    let memory_cleared = cache::clear().await;
+    let disk_cleared = cache::disk::clear().await;
+    let success = memory_cleared && disk_cleared;
+
+    crate::metrics::record_cache_clear(success);
+
+    if success {
+        tracing::info!("Cache cleared (memory + disk)");
         Ok(Json(CacheStatusResponse {
             success: true,
             message: "Cache cleared successfully".to_string(),
@@ -270,6 +490,94 @@ async fn clear_cache() -> Result<Json<CacheStatusResponse>> {
     }
 }
 
+/// Semantic search over a course's indexed section summaries and activity names
+///
+/// A course must have been fetched via `GET /api/courses/{id}` at least once
+/// so its content is indexed before it can be searched. Requires a token and
+/// checks it's enrolled in `course_id`, since (unlike the other routes here)
+/// the search index is local and doesn't forward the request to Moodle for
+/// Moodle's own enrollment check to apply.
+async fn search_content(
+    State(config): State<Config>,
+    Authed(auth): Authed,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<SearchResponse>> {
+    let client = MoodleClient::new(&config);
+    let courses = client.get_user_courses(&auth.token, auth.userid).await?;
+    if !courses.iter().any(|c| c.id == query.course_id) {
+        return Err(AppError::Unauthorized);
+    }
+
+    let top_k = query.top_k.unwrap_or(5).min(50);
+    let query_vector = embed_cached(&HashingEmbedder, &query.q).await?;
+
+    let results = INDEX.search(query.course_id, &query_vector, top_k).await
+        .into_iter()
+        .map(|scored| SearchResultItem {
+            section_id: scored.chunk.section_id,
+            section_name: scored.chunk.section_name,
+            module_id: scored.chunk.module_id,
+            module_name: scored.chunk.module_name,
+            text: scored.chunk.text,
+            score: scored.score,
+        })
+        .collect();
+
+    Ok(Json(SearchResponse { results }))
+}
+
+/// Max concurrent Moodle requests a single batch prefetch may hold open at once
+const BATCH_CONCURRENCY: usize = 10;
+
+/// Resolve one URL of a batch prefetch: cache lookup (with revalidation), else
+/// fetch-and-clean, reported as a `BatchPrefetchItem` either way so a single
+/// bad URL never fails the rest of the batch
+async fn fetch_batch_item(config: &Config, token: &str, url: String) -> BatchPrefetchItem {
+    let cache_key = format!("activity:{}", MemoryCache::url_hash(&url));
+    let client = MoodleClient::new(config);
+
+    let item = match lookup_cache(&client, token, &cache_key).await {
+        CacheLookup::Hit(body) => BatchPrefetchItem {
+            url,
+            success: true,
+            content: Some(body),
+            error: None,
+        },
+        CacheLookup::Stale { raw_html, source_url, validators } => {
+            let cleaned = clean_html_with_token(&raw_html, Some(token), false);
+            store_cache(&cache_key, cleaned.clone(), Some(source_url), validators).await;
+            BatchPrefetchItem {
+                url,
+                success: true,
+                content: Some(cleaned),
+                error: None,
+            }
+        }
+        CacheLookup::Miss => match fetch_activity_content(&client, token, &url).await {
+            Ok((content, source_url, validators)) => {
+                let cleaned = clean_html_with_token(&content, Some(token), false);
+                store_cache(&cache_key, cleaned.clone(), source_url, validators).await;
+
+                BatchPrefetchItem {
+                    url,
+                    success: true,
+                    content: Some(cleaned),
+                    error: None,
+                }
+            }
+            Err(e) => BatchPrefetchItem {
+                url,
+                success: false,
+                content: None,
+                error: Some(e),
+            },
+        },
+    };
+
+    crate::metrics::record_activity_fetch(item.success);
+    item
+}
+
 /// Batch prefetch multiple activities at once
 async fn batch_prefetch(
     State(config): State<Config>,
@@ -277,68 +585,32 @@ async fn batch_prefetch(
     Json(request): Json<BatchPrefetchRequest>,
 ) -> Result<Json<BatchPrefetchResponse>> {
     let token = extract_token(&headers)?;
-    
+
     tracing::info!("Batch prefetch request for {} URLs", request.urls.len());
-    
-    let client = MoodleClient::new(&config);
-    
+
     // Process URLs concurrently with a semaphore to limit parallelism
     use tokio::sync::Semaphore;
     use std::sync::Arc;
-    
-    let semaphore = Arc::new(Semaphore::new(10)); // Max 10 concurrent requests
-    
-    let futures: Vec<_> = request.urls.iter().map(|url| {
-        let url = url.clone();
+
+    let semaphore = Arc::new(Semaphore::new(BATCH_CONCURRENCY));
+
+    let futures: Vec<_> = request.urls.into_iter().map(|url| {
         let token = token.clone();
         let config = config.clone();
         let semaphore = semaphore.clone();
-        
+
         async move {
             let _permit = semaphore.acquire().await;
-            
-            // Check cache first
-            let cache_key = format!("activity:{}", MemoryCache::url_hash(&url));
-            if let Some(cached_content) = CACHE.get(&cache_key) {
-                return BatchPrefetchItem {
-                    url,
-                    success: true,
-                    content: Some(cached_content),
-                    error: None,
-                };
-            }
-            
-            // Fetch and clean content
-            let client = MoodleClient::new(&config);
-            match fetch_activity_content(&client, &token, &url).await {
-                Ok(content) => {
-                    let cleaned = clean_html_with_token(&content, Some(&token));
-                    CACHE.set(&cache_key, &cleaned, Some(Duration::from_secs(3600)));
-                    
-                    BatchPrefetchItem {
-                        url,
-                        success: true,
-                        content: Some(cleaned),
-                        error: None,
-                    }
-                }
-                Err(e) => {
-                    BatchPrefetchItem {
-                        url,
-                        success: false,
-                        content: None,
-                        error: Some(e.to_string()),
-                    }
-                }
-            }
+            fetch_batch_item(&config, &token, url).await
         }
     }).collect();
-    
+
     let items: Vec<BatchPrefetchItem> = join_all(futures).await;
     let loaded = items.iter().filter(|i| i.success).count();
-    
+
     tracing::info!("Batch prefetch complete: {}/{} loaded", loaded, items.len());
-    
+    crate::metrics::record_batch(items.len(), loaded);
+
     Ok(Json(BatchPrefetchResponse {
         success: true,
         total: items.len(),
@@ -347,29 +619,113 @@ async fn batch_prefetch(
     }))
 }
 
+/// Terminal SSE event for `batch_prefetch_stream`, once every URL has resolved
+#[derive(Debug, Serialize)]
+struct BatchPrefetchSummary {
+    total: usize,
+    loaded: usize,
+}
+
+/// Batch prefetch multiple activities, streaming one SSE `item` event per
+/// `BatchPrefetchItem` as soon as its future resolves - rather than `/batch`,
+/// which blocks on `join_all` until the slowest URL finishes - followed by a
+/// terminal `done` event carrying the `{total, loaded}` summary. Reuses the
+/// same semaphore-bounded, per-URL cache/fetch logic as `batch_prefetch`.
+async fn batch_prefetch_stream(
+    State(config): State<Config>,
+    headers: HeaderMap,
+    Json(request): Json<BatchPrefetchRequest>,
+) -> Result<Sse<impl Stream<Item = std::result::Result<Event, std::convert::Infallible>>>> {
+    let token = extract_token(&headers)?;
+    let total = request.urls.len();
+
+    tracing::info!("Streaming batch prefetch request for {} URLs", total);
+
+    use tokio::sync::Semaphore;
+    use std::sync::Arc;
+
+    let semaphore = Arc::new(Semaphore::new(BATCH_CONCURRENCY));
+
+    let futures: FuturesUnordered<_> = request.urls.into_iter().map(|url| {
+        let token = token.clone();
+        let config = config.clone();
+        let semaphore = semaphore.clone();
+
+        async move {
+            let _permit = semaphore.acquire().await;
+            fetch_batch_item(&config, &token, url).await
+        }
+    }).collect();
+
+    // `unfold` drives `futures` to completion one item at a time, emitting an
+    // `item` event per resolved future, then a final `done` summary once the
+    // set is drained
+    let stream = futures::stream::unfold(
+        (futures, 0usize, false),
+        |(mut pending, mut loaded, done)| async move {
+            if done {
+                return None;
+            }
+
+            match pending.next().await {
+                Some(item) => {
+                    if item.success {
+                        loaded += 1;
+                    }
+                    let event = Event::default()
+                        .event("item")
+                        .json_data(&item)
+                        .unwrap_or_else(|_| Event::default().event("item"));
+                    Some((Ok(event), (pending, loaded, false)))
+                }
+                None => {
+                    tracing::info!("Streaming batch prefetch complete: {}/{} loaded", loaded, total);
+                    crate::metrics::record_batch(total, loaded);
+                    let summary = BatchPrefetchSummary { total, loaded };
+                    let event = Event::default()
+                        .event("done")
+                        .json_data(&summary)
+                        .unwrap_or_else(|_| Event::default().event("done"));
+                    Some((Ok(event), (pending, loaded, true)))
+                }
+            }
+        },
+    );
+
+    Ok(Sse::new(stream))
+}
+
 /// Helper to fetch activity content (extracted from get_activity_content)
-async fn fetch_activity_content(client: &MoodleClient, token: &str, url: &str) -> std::result::Result<String, String> {
+///
+/// Returns the raw combined HTML along with a single source URL/validators
+/// pair when the content came from exactly one file, so the caller can cache
+/// it in a revalidatable form
+async fn fetch_activity_content(
+    client: &MoodleClient,
+    token: &str,
+    url: &str,
+) -> std::result::Result<(String, Option<String>, FileValidators), String> {
     // Extract module ID from URL
     let cmid = extract_module_id(url)
         .ok_or_else(|| "Invalid URL format".to_string())?;
-    
+
     // Get module info
     let mod_info = client.get_course_module(token, cmid).await
         .map_err(|e| format!("Failed to get module info: {}", e))?;
-    
+
     let course_id = mod_info
         .get("cm")
         .and_then(|cm| cm.get("course"))
         .and_then(|c| c.as_i64())
         .ok_or_else(|| "Could not extract course ID".to_string())?;
-    
+
     // Get course contents
     let sections = client.get_course_contents(token, course_id).await
         .map_err(|e| format!("Failed to get course contents: {}", e))?;
-    
+
     // Find HTML files for this module
     let mut html_files: Vec<(String, String)> = Vec::new();
-    
+
     for section in &sections {
         for module in &section.modules {
             if module.id == cmid {
@@ -390,25 +746,31 @@ async fn fetch_activity_content(client: &MoodleClient, token: &str, url: &str) -
             break;
         }
     }
-    
+
     if html_files.is_empty() {
         // Fallback: Try direct download
-        return client.download_file(token, url).await
-            .map_err(|e| format!("No HTML content: {}", e));
+        let (html, validators) = client.download_file_with_validators(token, url).await
+            .map_err(|e| format!("No HTML content: {}", e))?;
+        return Ok((html, Some(url.to_string()), validators));
     }
-    
+
     // Download and combine HTML files
     let mut combined_html = String::new();
+    let mut single_file_validators = None;
     for (fileurl, _) in &html_files {
-        if let Ok(html) = client.download_file(token, fileurl).await {
+        if let Ok((html, validators)) = client.download_file_with_validators(token, fileurl).await {
             combined_html.push_str(&html);
             combined_html.push_str("\n\n");
+            if html_files.len() == 1 {
+                single_file_validators = Some(validators);
+            }
         }
     }
-    
+
     if combined_html.is_empty() {
         return Err("Failed to download content".to_string());
     }
-    
-    Ok(combined_html)
+
+    let source_url = if html_files.len() == 1 { Some(html_files[0].0.clone()) } else { None };
+    Ok((combined_html, source_url, single_file_validators.unwrap_or_default()))
 }
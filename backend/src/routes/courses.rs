@@ -2,30 +2,30 @@
 
 use axum::{
     extract::{Path, State},
-    http::header::AUTHORIZATION,
     routing::get,
     Json, Router,
 };
-use axum::http::HeaderMap;
 use serde::Serialize;
+use utoipa::ToSchema;
 
+use crate::auth::Authed;
 use crate::config::Config;
-use crate::error::{AppError, Result};
+use crate::error::Result;
 use crate::moodle::{MoodleClient, Course, CourseSection, Activity};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CoursesResponse {
     courses: Vec<Course>,
     userid: i64,
     fullname: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CourseContentsResponse {
     sections: Vec<SectionWithActivities>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SectionWithActivities {
     #[serde(flatten)]
     section: CourseSection,
@@ -39,58 +39,29 @@ pub fn routes() -> Router<Config> {
         .route("/{id}", get(get_course_contents))
 }
 
-/// Extract token from Authorization header
-fn extract_token(headers: &HeaderMap) -> Result<String> {
-    let auth_header = headers.get(AUTHORIZATION);
-    
-    tracing::debug!("Authorization header present: {}", auth_header.is_some());
-    
-    let token = auth_header
-        .and_then(|v| v.to_str().ok())
-        .map(|v| v.trim_start_matches("Bearer ").to_string())
-        .ok_or(AppError::Unauthorized)?;
-    
-    tracing::debug!("Token extracted, length: {}", token.len());
-    
-    Ok(token)
-}
-
 /// Get user's enrolled courses
-/// 
-/// GET /api/courses
-/// Header: Authorization: Bearer <token>
-async fn get_courses(
+///
+/// Requires a session cookie or bearer token (takes `Authed`)
+#[utoipa::path(
+    get,
+    path = "/api/courses",
+    tag = "courses",
+    responses(
+        (status = 200, description = "Enrolled courses", body = CoursesResponse),
+        (status = 401, description = "Not authenticated", body = crate::error::ErrorResponse),
+    )
+)]
+pub(crate) async fn get_courses(
     State(config): State<Config>,
-    headers: HeaderMap,
+    Authed(auth): Authed,
 ) -> Result<Json<CoursesResponse>> {
     tracing::info!("GET /api/courses");
-    
-    let token = match extract_token(&headers) {
-        Ok(t) => t,
-        Err(e) => {
-            tracing::warn!("Token extraction failed: {}", e);
-            return Err(e);
-        }
-    };
-    
+
     let client = MoodleClient::new(&config);
-    
-    // Get site info for user ID
-    tracing::debug!("Fetching site info...");
-    let site_info = match client.get_site_info(&token).await {
-        Ok(info) => {
-            tracing::info!("Site info retrieved for user: {}", info.fullname);
-            info
-        }
-        Err(e) => {
-            tracing::error!("Failed to get site info: {}", e);
-            return Err(e);
-        }
-    };
-    
+
     // Get courses
-    tracing::debug!("Fetching courses for user {}...", site_info.userid);
-    let courses = match client.get_user_courses(&token, site_info.userid).await {
+    tracing::debug!("Fetching courses for user {}...", auth.userid);
+    let courses = match client.get_user_courses(&auth.token, auth.userid).await {
         Ok(c) => {
             tracing::info!("Fetched {} courses", c.len());
             c
@@ -100,30 +71,41 @@ async fn get_courses(
             return Err(e);
         }
     };
-    
+
     Ok(Json(CoursesResponse {
         courses,
-        userid: site_info.userid,
-        fullname: site_info.fullname,
+        userid: auth.userid,
+        fullname: auth.fullname,
     }))
 }
 
 /// Get course contents (sections and activities)
-/// 
-/// GET /api/courses/:id
-/// Header: Authorization: Bearer <token>
-async fn get_course_contents(
+///
+/// Requires a session cookie or bearer token (takes `Authed`)
+#[utoipa::path(
+    get,
+    path = "/api/courses/{id}",
+    tag = "courses",
+    params(("id" = i64, Path, description = "Moodle course id")),
+    responses(
+        (status = 200, description = "Course sections and activities", body = CourseContentsResponse),
+        (status = 401, description = "Not authenticated", body = crate::error::ErrorResponse),
+    )
+)]
+pub(crate) async fn get_course_contents(
     State(config): State<Config>,
-    headers: HeaderMap,
+    Authed(auth): Authed,
     Path(id): Path<i64>,
 ) -> Result<Json<CourseContentsResponse>> {
     tracing::info!("GET /api/courses/{}", id);
-    
-    let token = extract_token(&headers)?;
+
     let client = MoodleClient::new(&config);
-    
-    let sections = client.get_course_contents(&token, id).await?;
-    
+
+    let sections = client.get_course_contents(&auth.token, id).await?;
+
+    // Index section summaries/activity names for semantic search (`/content/search`)
+    crate::embeddings::index_course(id, &sections).await;
+
     // Transform sections to include activities
     let sections_with_activities: Vec<SectionWithActivities> = sections
         .into_iter()
@@ -1,151 +1,273 @@
 //! Export routes for PDF generation
+//!
+//! `POST /pdf` enqueues a background Typst compile job instead of blocking
+//! the request thread, so a large course export doesn't tie up a connection.
 
 use axum::{
-    extract::State,
-    http::{header, HeaderMap, StatusCode},
-    routing::post,
+    extract::Path,
+    http::{header, StatusCode},
+    routing::{get, post},
     Json, Router,
     response::IntoResponse,
 };
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
-use std::process::Command;
-use tempfile::NamedTempFile;
 use std::io::Write;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use utoipa::ToSchema;
 
+use crate::auth::Authed;
+use crate::cache::jobs::{JobStatus, JOBS};
+use crate::citation::{self, BibEntry};
 use crate::config::Config;
 use crate::content::typst::{html_to_typst, generate_typst_document};
+use crate::moodle::MoodleClient;
 
-#[derive(Debug, Deserialize)]
+/// How long a finished job's bytes stay downloadable before eviction
+const JOB_TTL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Deserialize, Clone, ToSchema)]
 pub struct ExportPdfRequest {
     pub title: String,
     pub sections: Vec<ExportSection>,
+    /// Bibliography entries to render as `#bibliography(...)`, parsed from
+    /// BibTeX/RIS or synthesized from a chosen LibGen result
+    #[serde(default)]
+    pub bibliography: Vec<BibEntry>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone, ToSchema)]
 pub struct ExportSection {
     pub name: String,
     /// HTML content (already fetched/cleaned by frontend)
     pub content: String,
+    /// Bibliography key to cite at the end of this section, e.g. `[@key]`
+    #[serde(default)]
+    pub citation_key: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ExportErrorResponse {
     pub success: bool,
     pub error: String,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EnqueuedResponse {
+    pub job_id: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JobStatusResponse {
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct ExportJob {
+    job_id: String,
+    request: ExportPdfRequest,
+    /// Moodle token of the requesting user, needed to download embedded images
+    token: String,
+    /// Moodle userid of the requesting user, so the job can only be polled/downloaded by them
+    userid: i64,
+}
+
+static WORKER_TX: OnceLock<mpsc::UnboundedSender<ExportJob>> = OnceLock::new();
+
+/// Spawn the background worker that compiles queued PDF exports
+///
+/// Must be called once at startup before any `/pdf` request is served.
+pub fn spawn_worker(config: Config) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<ExportJob>();
+    WORKER_TX.set(tx).ok();
+
+    tokio::spawn(async move {
+        let client = MoodleClient::new(&config);
+
+        while let Some(job) = rx.recv().await {
+            JOBS.set_status(&job.job_id, job.userid, JobStatus::Running, JOB_TTL);
+
+            match compile_pdf(&job.request, &client, &job.token).await {
+                Ok(bytes) => {
+                    tracing::info!("Export job {} finished: {} bytes", job.job_id, bytes.len());
+                    JOBS.set_status(&job.job_id, job.userid, JobStatus::Done(bytes), JOB_TTL);
+                }
+                Err(e) => {
+                    tracing::error!("Export job {} failed: {}", job.job_id, e);
+                    JOBS.set_status(&job.job_id, job.userid, JobStatus::Failed(e), JOB_TTL);
+                }
+            }
+        }
+    });
+}
+
 /// Build export routes
 pub fn routes() -> Router<Config> {
     Router::new()
         .route("/pdf", post(export_pdf))
+        .route("/pdf/{job_id}", get(get_export_status))
+        .route("/pdf/{job_id}/download", get(download_export))
 }
 
-/// Export course content as PDF
+/// Enqueue course content for PDF export
 /// Expects pre-fetched HTML content from frontend
-async fn export_pdf(
+///
+/// Requires a session cookie or bearer token (takes `Authed`)
+#[utoipa::path(
+    post,
+    path = "/api/export/pdf",
+    tag = "export",
+    request_body = ExportPdfRequest,
+    responses(
+        (status = 202, description = "Export job enqueued", body = EnqueuedResponse),
+        (status = 401, description = "Not authenticated", body = crate::error::ErrorResponse),
+        (status = 503, description = "Export worker not running", body = ExportErrorResponse),
+    )
+)]
+pub(crate) async fn export_pdf(
+    Authed(auth): Authed,
     Json(request): Json<ExportPdfRequest>,
-) -> std::result::Result<impl IntoResponse, (StatusCode, Json<ExportErrorResponse>)> {
-    
+) -> std::result::Result<(StatusCode, Json<EnqueuedResponse>), (StatusCode, Json<ExportErrorResponse>)> {
     tracing::info!("PDF export request: {} sections, title: {}", request.sections.len(), request.title);
-    
-    // Convert each section's HTML to Typst
+
+    let Some(tx) = WORKER_TX.get() else {
+        return Err((StatusCode::SERVICE_UNAVAILABLE, Json(ExportErrorResponse {
+            success: false,
+            error: "Export worker is not running".to_string(),
+        })));
+    };
+
+    let job_id = generate_job_id();
+    JOBS.insert_pending(&job_id, auth.userid, JOB_TTL);
+
+    tx.send(ExportJob { job_id: job_id.clone(), request, token: auth.token.clone(), userid: auth.userid }).map_err(|_| {
+        (StatusCode::SERVICE_UNAVAILABLE, Json(ExportErrorResponse {
+            success: false,
+            error: "Export worker is not running".to_string(),
+        }))
+    })?;
+
+    Ok((StatusCode::ACCEPTED, Json(EnqueuedResponse { job_id })))
+}
+
+/// Poll the status of a PDF export job
+///
+/// Requires `Authed`; a job can only be polled by the user who enqueued it
+#[utoipa::path(
+    get,
+    path = "/api/export/pdf/{job_id}",
+    tag = "export",
+    params(("job_id" = String, Path, description = "Job id returned by POST /pdf")),
+    responses(
+        (status = 200, description = "Job status", body = JobStatusResponse),
+        (status = 401, description = "Not authenticated", body = crate::error::ErrorResponse),
+        (status = 404, description = "Unknown or expired job, or owned by another user", body = crate::error::ErrorResponse),
+    )
+)]
+pub(crate) async fn get_export_status(Authed(auth): Authed, Path(job_id): Path<String>) -> std::result::Result<Json<JobStatusResponse>, StatusCode> {
+    match JOBS.get(&job_id, auth.userid) {
+        Some(JobStatus::Pending) => Ok(Json(JobStatusResponse { status: "pending", error: None })),
+        Some(JobStatus::Running) => Ok(Json(JobStatusResponse { status: "running", error: None })),
+        Some(JobStatus::Done(_)) => Ok(Json(JobStatusResponse { status: "done", error: None })),
+        Some(JobStatus::Failed(e)) => Ok(Json(JobStatusResponse { status: "failed", error: Some(e) })),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Download the finished PDF for a completed export job
+///
+/// Requires `Authed`; a job can only be downloaded by the user who enqueued it
+#[utoipa::path(
+    get,
+    path = "/api/export/pdf/{job_id}/download",
+    tag = "export",
+    params(("job_id" = String, Path, description = "Job id returned by POST /pdf")),
+    responses(
+        (status = 200, description = "Generated PDF", content_type = "application/pdf"),
+        (status = 401, description = "Not authenticated"),
+        (status = 404, description = "Job not found, not finished, failed, or owned by another user"),
+    )
+)]
+pub(crate) async fn download_export(Authed(auth): Authed, Path(job_id): Path<String>) -> std::result::Result<impl IntoResponse, StatusCode> {
+    match JOBS.get(&job_id, auth.userid) {
+        Some(JobStatus::Done(bytes)) => {
+            let content_disposition = format!("attachment; filename=\"{}.pdf\"", job_id);
+            Ok((
+                StatusCode::OK,
+                [
+                    (header::CONTENT_TYPE, "application/pdf".to_string()),
+                    (header::CONTENT_DISPOSITION, content_disposition),
+                ],
+                bytes,
+            ))
+        }
+        _ => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// Convert sections to Typst, compile with Typst, and return the PDF bytes
+async fn compile_pdf(request: &ExportPdfRequest, client: &MoodleClient, token: &str) -> Result<Vec<u8>, String> {
+    // Both the .typ file and any downloaded image assets live in this one temp
+    // dir so Typst can resolve `#figure(image("..."))` paths at compile time.
+    let work_dir = tempfile::tempdir().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let assets_dir = work_dir.path().join("assets");
+
     let mut typst_sections: Vec<(String, String)> = Vec::new();
-    
+
     for section in &request.sections {
         if !section.content.is_empty() {
-            let typst_content = html_to_typst(&section.content);
+            let mut typst_content = html_to_typst(&section.content, client, token, &assets_dir).await;
+            if !request.bibliography.is_empty() {
+                typst_content = citation::insert_markers(&typst_content, &request.bibliography);
+            }
+            if let Some(key) = &section.citation_key {
+                typst_content.push_str(&format!(" [@{}]", key));
+            }
             if !typst_content.trim().is_empty() {
                 typst_sections.push((section.name.clone(), typst_content));
-                tracing::debug!("Converted section '{}': {} chars", section.name, section.content.len());
             }
         }
     }
-    
+
     if typst_sections.is_empty() {
-        return Err((StatusCode::BAD_REQUEST, Json(ExportErrorResponse {
-            success: false,
-            error: "No content to export".to_string(),
-        })));
+        return Err("No content to export".to_string());
     }
-    
-    tracing::info!("Converting {} sections to PDF", typst_sections.len());
-    
-    // Generate Typst document
-    let typst_content = generate_typst_document(&request.title, typst_sections);
-    
-    // Write to temp file
-    let mut typst_file = NamedTempFile::with_suffix(".typ").map_err(|e| {
-        tracing::error!("Failed to create temp file: {}", e);
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(ExportErrorResponse {
-            success: false,
-            error: "Failed to create temp file".to_string(),
-        }))
-    })?;
-    
-    typst_file.write_all(typst_content.as_bytes()).map_err(|e| {
-        tracing::error!("Failed to write typst content: {}", e);
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(ExportErrorResponse {
-            success: false,
-            error: "Failed to write content".to_string(),
-        }))
-    })?;
-    
-    let typst_path = typst_file.path();
+
+    let typst_content = generate_typst_document(&request.title, typst_sections, &request.bibliography, work_dir.path());
+
+    let mut typst_file = tempfile::Builder::new()
+        .suffix(".typ")
+        .tempfile_in(work_dir.path())
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+
+    typst_file.write_all(typst_content.as_bytes())
+        .map_err(|e| format!("Failed to write typst content: {}", e))?;
+
+    let typst_path = typst_file.path().to_path_buf();
     let pdf_path = typst_path.with_extension("pdf");
-    
-    // Compile with Typst
-    let output = Command::new("typst")
+
+    let output = tokio::process::Command::new("typst")
         .args(["compile", typst_path.to_str().unwrap(), pdf_path.to_str().unwrap()])
         .output()
-        .map_err(|e| {
-            tracing::error!("Failed to run typst: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, Json(ExportErrorResponse {
-                success: false,
-                error: format!("Failed to run typst: {}", e),
-            }))
-        })?;
-    
+        .await
+        .map_err(|e| format!("Failed to run typst: {}", e))?;
+
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        tracing::error!("Typst compilation failed: {}", stderr);
-        return Err((StatusCode::INTERNAL_SERVER_ERROR, Json(ExportErrorResponse {
-            success: false,
-            error: format!("Typst compilation failed: {}", stderr),
-        })));
+        return Err(format!("Typst compilation failed: {}", stderr));
     }
-    
-    // Read PDF
-    let pdf_bytes = std::fs::read(&pdf_path).map_err(|e| {
-        tracing::error!("Failed to read PDF: {}", e);
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(ExportErrorResponse {
-            success: false,
-            error: "Failed to read generated PDF".to_string(),
-        }))
-    })?;
-    
-    // Clean up temp PDF
+
+    let pdf_bytes = std::fs::read(&pdf_path).map_err(|e| format!("Failed to read PDF: {}", e))?;
     let _ = std::fs::remove_file(&pdf_path);
-    
-    tracing::info!("PDF generated successfully: {} bytes", pdf_bytes.len());
-    
-    // Return PDF with proper headers
-    let filename = format!("{}.pdf", sanitize_filename(&request.title));
-    let content_disposition = format!("attachment; filename=\"{}\"", filename);
-    
-    Ok((
-        StatusCode::OK,
-        [
-            (header::CONTENT_TYPE, "application/pdf".to_string()),
-            (header::CONTENT_DISPOSITION, content_disposition),
-        ],
-        pdf_bytes,
-    ))
+
+    Ok(pdf_bytes)
 }
 
-fn sanitize_filename(name: &str) -> String {
-    name.chars()
-        .map(|c| if c.is_alphanumeric() || c == ' ' || c == '-' || c == '_' { c } else { '_' })
-        .collect::<String>()
-        .trim()
-        .to_string()
+fn generate_job_id() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
 }
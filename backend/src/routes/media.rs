@@ -0,0 +1,158 @@
+//! Authenticated media proxy for protected Moodle file content
+//!
+//! `ModuleContent` carries a `fileurl`, but Moodle requires the caller's
+//! webservice token appended as `?token=` before it'll serve the bytes - so
+//! the frontend can never fetch it directly without the raw token leaking.
+//! This route resolves the file server-side using the caller's session, then
+//! streams the upstream response through unchanged (status, `Content-Type`,
+//! `Content-Length`, and `Range`/`Content-Range` for seeking), caching small
+//! files so repeat views skip the round trip to Moodle.
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::auth::Authed;
+use crate::cache::{self, MemoryCache};
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::moodle::MoodleClient;
+
+/// Cached bodies are held base64-encoded in memory; anything bigger isn't
+/// worth caching (most course files are small documents/images anyway).
+const MAX_CACHEABLE_BYTES: u64 = 5 * 1024 * 1024;
+const MEDIA_CACHE_TTL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Deserialize)]
+pub struct MediaQuery {
+    path: String,
+}
+
+/// Build media routes
+pub fn routes() -> Router<Config> {
+    Router::new().route("/{contextid}/{module}/file", get(get_media))
+}
+
+/// Stream (and opportunistically cache) a protected Moodle file
+///
+/// Requires a session cookie or bearer token (takes `Authed`)
+#[utoipa::path(
+    get,
+    path = "/api/media/{contextid}/{module}/file",
+    tag = "media",
+    params(
+        ("contextid" = i64, Path, description = "Moodle context id"),
+        ("module" = String, Path, description = "Moodle component, e.g. mod_resource"),
+        ("path" = String, Query, description = "Remainder of the pluginfile.php path (filearea/itemid/filename)"),
+    ),
+    responses(
+        (status = 200, description = "File contents", content_type = "application/octet-stream"),
+        (status = 206, description = "Partial file contents (range request)"),
+        (status = 401, description = "Not authenticated", body = crate::error::ErrorResponse),
+        (status = 404, description = "File not found", body = crate::error::ErrorResponse),
+    )
+)]
+pub(crate) async fn get_media(
+    State(config): State<Config>,
+    Authed(auth): Authed,
+    Path((contextid, module)): Path<(i64, String)>,
+    Query(query): Query<MediaQuery>,
+    headers: HeaderMap,
+) -> Result<Response> {
+    let file_url = format!(
+        "{}/webservice/pluginfile.php/{}/{}/{}",
+        config.moodle_url,
+        contextid,
+        module,
+        query.path.trim_start_matches('/'),
+    );
+
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+
+    let cache_key = format!("media:{}", MemoryCache::url_hash(&file_url));
+
+    // Range requests must hit Moodle directly so seeking stays accurate.
+    if range.is_none() {
+        if let Some(cached) = cache::get(&cache_key).await.and_then(|v| decode_cached(&v)) {
+            let (content_type, bytes) = cached;
+            return Ok(media_response(StatusCode::OK, &content_type, bytes));
+        }
+    }
+
+    let client = MoodleClient::new(&config);
+    let upstream = client.fetch_file(&auth.token, &file_url, range.as_deref()).await?;
+
+    let status = upstream.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(AppError::NotFound(format!("Media file not found: {}", file_url)));
+    }
+    if !status.is_success() {
+        return Err(AppError::MoodleApi(format!("Failed to fetch media: HTTP {}", status)));
+    }
+
+    let content_type = upstream
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    let content_length = upstream.content_length();
+    let content_range = upstream
+        .headers()
+        .get(header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let status_code = StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::OK);
+
+    let cacheable = range.is_none() && content_length.is_some_and(|len| len <= MAX_CACHEABLE_BYTES);
+    if cacheable {
+        let bytes = upstream.bytes().await.map_err(AppError::Request)?;
+        cache::set(&cache_key, &encode_cached(&content_type, &bytes), Some(MEDIA_CACHE_TTL)).await;
+        return Ok(media_response(status_code, &content_type, bytes.to_vec()));
+    }
+
+    let mut builder = Response::builder().status(status_code).header(header::CONTENT_TYPE, content_type);
+    if let Some(len) = content_length {
+        builder = builder.header(header::CONTENT_LENGTH, len);
+    }
+    if let Some(content_range) = content_range {
+        builder = builder.header(header::CONTENT_RANGE, content_range).header(header::ACCEPT_RANGES, "bytes");
+    }
+
+    builder
+        .body(Body::from_stream(upstream.bytes_stream()))
+        .map_err(|e| AppError::Internal(format!("Failed to build media response: {}", e)))
+}
+
+fn media_response(status: StatusCode, content_type: &str, bytes: Vec<u8>) -> Response {
+    (
+        status,
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_LENGTH, bytes.len().to_string()),
+        ],
+        bytes,
+    )
+        .into_response()
+}
+
+fn encode_cached(content_type: &str, bytes: &[u8]) -> String {
+    format!("{}|{}", content_type, STANDARD.encode(bytes))
+}
+
+fn decode_cached(value: &str) -> Option<(String, Vec<u8>)> {
+    let (content_type, data) = value.split_once('|')?;
+    let bytes = STANDARD.decode(data).ok()?;
+    Some((content_type.to_string(), bytes))
+}
@@ -1,10 +1,11 @@
 //! API Routes
 
-mod auth;
-mod courses;
+pub mod auth;
+pub mod courses;
 mod content;
 mod books;
-mod export;
+pub mod export;
+pub mod media;
 
 use axum::Router;
 use crate::config::Config;
@@ -17,5 +18,6 @@ pub fn api_routes() -> Router<Config> {
         .nest("/content", content::routes())
         .nest("/books", books::books_routes())
         .nest("/export", export::routes())
+        .nest("/media", media::routes())
 }
 
@@ -0,0 +1,164 @@
+//! Server-side sessions backed by the cache layer, authenticated with signed cookies
+//!
+//! On login we mint an opaque session id, store the session data in the `cache`
+//! module, and hand the client an HMAC-signed cookie referencing it. Route
+//! handlers then extract a `Session` instead of re-validating a raw Moodle
+//! token on every request.
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header::COOKIE, request::Parts, HeaderMap, HeaderValue},
+};
+use hmac::{Hmac, Mac};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::cache::{self, crypto};
+use crate::config::Config;
+use crate::error::AppError;
+
+const SESSION_COOKIE: &str = "mylms_session";
+const SESSION_TTL_SECS: u64 = 60 * 60 * 24 * 7;
+
+/// Data stored in the cache for an active session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionData {
+    pub moodle_token: String,
+    pub userid: i64,
+    pub fullname: String,
+    pub expires_at: u64,
+}
+
+/// An authenticated session, extracted from a signed cookie
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub id: String,
+    pub data: SessionData,
+}
+
+fn cache_key(session_id: &str) -> String {
+    format!("session:{}", session_id)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+fn generate_session_id() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn sign(secret: &str, session_id: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts a key of any size");
+    mac.update(session_id.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+fn verify_cookie_value(secret: &str, value: &str) -> Option<String> {
+    let (session_id, signature) = value.split_once('.')?;
+    let signature_bytes = hex::decode(signature).ok()?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts a key of any size");
+    mac.update(session_id.as_bytes());
+    // `verify_slice` compares in constant time, unlike `==` on the decoded
+    // bytes, which would leak how many leading bytes of the signature matched
+    mac.verify_slice(&signature_bytes).ok()?;
+
+    Some(session_id.to_string())
+}
+
+/// Create a new session for a logged-in user, returning the `Set-Cookie` header value
+pub async fn create_session(config: &Config, moodle_token: String, userid: i64, fullname: String) -> Result<HeaderValue, AppError> {
+    let session_id = generate_session_id();
+    let expires_at = now_secs() + SESSION_TTL_SECS;
+
+    let data = SessionData {
+        moodle_token,
+        userid,
+        fullname,
+        expires_at,
+    };
+
+    let json = serde_json::to_string(&data).expect("SessionData always serializes");
+    let encrypted = crypto::encrypt(&json)?;
+    cache::set(&cache_key(&session_id), &encrypted, Some(Duration::from_secs(SESSION_TTL_SECS))).await;
+
+    let signature = sign(&config.session_secret, &session_id);
+    let cookie = format!(
+        "{}={}.{}; HttpOnly; SameSite=Lax; Path=/; Max-Age={}",
+        SESSION_COOKIE, session_id, signature, SESSION_TTL_SECS
+    );
+
+    Ok(HeaderValue::from_str(&cookie).expect("cookie header is valid ASCII"))
+}
+
+/// Evict a session from the cache
+pub async fn evict_session(session_id: &str) {
+    cache::delete(&cache_key(session_id)).await;
+}
+
+/// `Set-Cookie` header value that clears the session cookie
+pub fn clear_cookie() -> HeaderValue {
+    HeaderValue::from_static("mylms_session=; HttpOnly; SameSite=Lax; Path=/; Max-Age=0")
+}
+
+/// Load and verify a session from a request's `Cookie` header
+///
+/// Shared by the `Session` extractor and the generic `auth::AuthBackend` layer
+/// so both paths agree on what counts as "logged in". Returns `Ok(None)` when
+/// there's simply no (valid) session cookie, but propagates an `AppError::Cache`
+/// if a cookie resolves to a cache entry that fails to decrypt - that's a
+/// tampered or corrupted record, not "logged out".
+pub async fn load_from_headers(config: &Config, headers: &HeaderMap) -> Result<Option<Session>, AppError> {
+    let cookie_header = headers.get(COOKIE).and_then(|v| v.to_str().ok()).unwrap_or("");
+
+    let Some(raw_value) = cookie_header
+        .split(';')
+        .map(|p| p.trim())
+        .find_map(|p| p.strip_prefix(&format!("{}=", SESSION_COOKIE)))
+    else {
+        return Ok(None);
+    };
+
+    let Some(session_id) = verify_cookie_value(&config.session_secret, raw_value) else {
+        return Ok(None);
+    };
+
+    let Some(encrypted) = cache::get(&cache_key(&session_id)).await else {
+        return Ok(None);
+    };
+
+    let json = crypto::decrypt(&encrypted)?;
+    let Some(data) = serde_json::from_str::<SessionData>(&json).ok() else {
+        return Ok(None);
+    };
+
+    if data.expires_at < now_secs() {
+        evict_session(&session_id).await;
+        return Ok(None);
+    }
+
+    Ok(Some(Session { id: session_id, data }))
+}
+
+impl<S> FromRequestParts<S> for Session
+where
+    Config: axum::extract::FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let config = Config::from_ref(state);
+        load_from_headers(&config, &parts.headers).await?.ok_or(AppError::Unauthorized)
+    }
+}